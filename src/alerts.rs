@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+// MARK: Levels
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+  #[default]
+  Normal,
+  Warning,
+  Critical,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+  CpuTemp,  // °C
+  GpuTemp,  // °C
+  CpuPower, // W
+  GpuPower, // W
+  AnePower, // W
+  AllPower, // W
+  SysPower, // W
+  Ram,      // % of total
+  Swap,     // GiB used
+}
+
+impl Metric {
+  pub fn label(&self) -> &'static str {
+    match self {
+      Metric::CpuTemp => "CPU temp",
+      Metric::GpuTemp => "GPU temp",
+      Metric::CpuPower => "CPU power",
+      Metric::GpuPower => "GPU power",
+      Metric::AnePower => "ANE power",
+      Metric::AllPower => "Power",
+      Metric::SysPower => "Total power",
+      Metric::Ram => "RAM",
+      Metric::Swap => "Swap",
+    }
+  }
+}
+
+// Both bounds are inclusive, missing one is never reached
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct Threshold {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub warning: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub critical: Option<f64>,
+}
+
+impl Threshold {
+  pub fn new(warning: f64, critical: f64) -> Self {
+    Self { warning: Some(warning), critical: Some(critical) }
+  }
+
+  pub fn level(&self, value: f64) -> Level {
+    match value {
+      x if self.critical.is_some_and(|c| x >= c) => Level::Critical,
+      x if self.warning.is_some_and(|w| x >= w) => Level::Warning,
+      _ => Level::Normal,
+    }
+  }
+
+  // Fields set in `self` take precedence
+  fn or(&self, other: &Threshold) -> Threshold {
+    Threshold {
+      warning: self.warning.or(other.warning),
+      critical: self.critical.or(other.critical),
+    }
+  }
+}
+
+// MARK: Defaults
+
+// (cpu, gpu) power limits by chip tier, taken from typical sustained load of each variant
+fn power_defaults(chip_name: &str) -> Option<(Threshold, Threshold)> {
+  let chip = chip_name.to_lowercase();
+  if !chip.starts_with("apple m") {
+    return None;
+  }
+
+  let limits = match chip {
+    x if x.ends_with(" ultra") => (Threshold::new(60.0, 90.0), Threshold::new(80.0, 120.0)),
+    x if x.ends_with(" max") => (Threshold::new(30.0, 45.0), Threshold::new(40.0, 60.0)),
+    x if x.ends_with(" pro") => (Threshold::new(25.0, 35.0), Threshold::new(20.0, 30.0)),
+    _ => (Threshold::new(12.0, 18.0), Threshold::new(10.0, 15.0)),
+  };
+
+  Some(limits)
+}
+
+pub fn default_thresholds(chip_name: &str) -> BTreeMap<Metric, Threshold> {
+  let mut items = BTreeMap::from([
+    (Metric::CpuTemp, Threshold::new(90.0, 100.0)),
+    (Metric::GpuTemp, Threshold::new(90.0, 100.0)),
+    (Metric::Ram, Threshold::new(85.0, 95.0)),
+    (Metric::Swap, Threshold::new(2.0, 8.0)),
+  ]);
+
+  if let Some((cpu, gpu)) = power_defaults(chip_name) {
+    let all = Threshold::new(
+      cpu.warning.unwrap_or(0.0) + gpu.warning.unwrap_or(0.0),
+      cpu.critical.unwrap_or(0.0) + gpu.critical.unwrap_or(0.0),
+    );
+
+    items.insert(Metric::CpuPower, cpu);
+    items.insert(Metric::GpuPower, gpu);
+    items.insert(Metric::AllPower, all);
+  }
+
+  items
+}
+
+// MARK: Thresholds
+
+// Effective thresholds: chip defaults overridden by user config
+#[derive(Debug, Clone, Default)]
+pub struct Thresholds {
+  items: BTreeMap<Metric, Threshold>,
+}
+
+impl Thresholds {
+  pub fn new(chip_name: &str, user: &BTreeMap<Metric, Threshold>) -> Self {
+    let mut items = default_thresholds(chip_name);
+    for (metric, threshold) in user {
+      let base = items.get(metric).copied().unwrap_or_default();
+      items.insert(*metric, threshold.or(&base));
+    }
+
+    Self { items }
+  }
+
+  pub fn level(&self, metric: Metric, value: f64) -> Level {
+    self.items.get(&metric).map_or(Level::Normal, |x| x.level(value))
+  }
+
+  // Metrics above warning threshold, worst first
+  pub fn alerts(&self, values: &[(Metric, f64)]) -> Vec<(Metric, Level)> {
+    let mut items = values
+      .iter()
+      .map(|(metric, value)| (*metric, self.level(*metric, *value)))
+      .filter(|x| x.1 != Level::Normal)
+      .collect::<Vec<_>>();
+
+    items.sort_by_key(|x| std::cmp::Reverse(x.1));
+    items
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_threshold_level() {
+    let t = Threshold::new(80.0, 100.0);
+    assert_eq!(t.level(79.9), Level::Normal);
+    assert_eq!(t.level(80.0), Level::Warning);
+    assert_eq!(t.level(100.0), Level::Critical);
+
+    let t = Threshold { warning: None, critical: Some(10.0) };
+    assert_eq!(t.level(9.0), Level::Normal);
+    assert_eq!(t.level(12.0), Level::Critical);
+  }
+
+  #[test]
+  fn test_chip_defaults() {
+    let base = default_thresholds("Apple M2");
+    let max = default_thresholds("Apple M3 Max");
+    assert!(base[&Metric::CpuPower].critical < max[&Metric::CpuPower].critical);
+    assert!(base.contains_key(&Metric::AllPower));
+
+    // unknown chip gets no power limits, but keeps temperature and memory ones
+    let other = default_thresholds("Unknown");
+    assert!(!other.contains_key(&Metric::CpuPower));
+    assert!(other.contains_key(&Metric::CpuTemp));
+    assert!(other.contains_key(&Metric::Swap));
+  }
+
+  #[test]
+  fn test_user_overrides() {
+    let user = BTreeMap::from([
+      (Metric::CpuTemp, Threshold { warning: Some(70.0), critical: None }),
+      (Metric::AnePower, Threshold::new(4.0, 6.0)),
+    ]);
+
+    let t = Thresholds::new("Apple M1", &user);
+    assert_eq!(t.level(Metric::CpuTemp, 75.0), Level::Warning);
+    assert_eq!(t.level(Metric::CpuTemp, 100.0), Level::Critical); // default critical kept
+    assert_eq!(t.level(Metric::AnePower, 7.0), Level::Critical);
+    assert_eq!(t.level(Metric::SysPower, 1000.0), Level::Normal); // no threshold
+  }
+
+  #[test]
+  fn test_alerts_order() {
+    let t = Thresholds::new("Apple M1", &BTreeMap::new());
+    let values = [(Metric::CpuTemp, 95.0), (Metric::Ram, 50.0), (Metric::Swap, 10.0)];
+
+    let alerts = t.alerts(&values);
+    assert_eq!(alerts, vec![(Metric::Swap, Level::Critical), (Metric::CpuTemp, Level::Warning)]);
+  }
+}
@@ -1,14 +1,27 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::{io::stdout, time::Instant};
 use std::{sync::mpsc, time::Duration};
 
 use ratatui::crossterm::{
-  event::{self, KeyCode, KeyModifiers},
+  event::{self, KeyCode, MouseButton, MouseEventKind},
   terminal, ExecutableCommand,
 };
 use ratatui::{prelude::*, widgets::*};
 
-use crate::config::{Config, ViewType};
+use crate::alerts::{Level, Metric, Thresholds};
+use crate::config::{
+  Config, GroupTitle, LayoutNode, PanelKind, ViewType, MAX_INTERVAL, MIN_INTERVAL,
+};
+use crate::fans::{fmt_fans, FanMetrics};
+use crate::history::{now_ms, Series, TierSpec};
+use crate::keys::{Action, KeyMap};
 use crate::metrics::{zero_div, Metrics, Sampler};
+use crate::sensors::{SensorsConfig, CPU, GPU};
+use crate::snapshot::{self, Format};
+use crate::summary::Session;
+use crate::theme::{ascii_chart, Theme, ASCII_BAR, ASCII_BORDER, ASCII_LINE};
+use crate::units::{Units, GIB};
 use crate::{
   metrics::{MemMetrics, TempMetrics},
   sources::SocInfo,
@@ -16,8 +29,23 @@ use crate::{
 
 type WithError<T> = Result<T, Box<dyn std::error::Error>>;
 
-const GB: u64 = 1024 * 1024 * 1024;
-const MAX_SPARKLINE: usize = 128;
+// terminal size thresholds for automatic compact modes
+const LINE_MAX_HEIGHT: u16 = 2;
+const MINI_MAX_HEIGHT: u16 = 9;
+const STACK_MAX_WIDTH: u16 = 59;
+
+// columns per core in `cpu_cores` panel, e.g. "P10 100% ──"
+const CORE_CELL_WIDTH: u16 = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Compact {
+  /// Single line summary
+  Line,
+  /// 2-3 lines with usage gauges
+  Mini,
+  /// Panels in one column for narrow panes
+  Stack,
+}
 
 // MARK: Term utils
 
@@ -29,6 +57,7 @@ fn enter_term() -> Terminal<impl Backend> {
 
   terminal::enable_raw_mode().unwrap();
   stdout().execute(terminal::EnterAlternateScreen).unwrap();
+  stdout().execute(event::EnableMouseCapture).unwrap();
 
   let term = CrosstermBackend::new(std::io::stdout());
   let term = Terminal::new(term).unwrap();
@@ -36,114 +65,316 @@ fn enter_term() -> Terminal<impl Backend> {
 }
 
 fn leave_term() {
+  stdout().execute(event::DisableMouseCapture).unwrap();
   terminal::disable_raw_mode().unwrap();
   stdout().execute(terminal::LeaveAlternateScreen).unwrap();
 }
 
-// MARK: Storage
+// Restores the terminal on every exit path, panics are handled by the hook
+struct TermGuard;
 
-fn items_add<T>(vec: &mut Vec<T>, val: T) -> &Vec<T> {
-  vec.insert(0, val);
-  if vec.len() > MAX_SPARKLINE {
-    vec.pop();
+impl Drop for TermGuard {
+  fn drop(&mut self) {
+    if !std::thread::panicking() {
+      leave_term();
+    }
   }
-  vec
+}
+
+// MARK: Storage
+
+// Part of history on screen: live tail (`cursor` is None) or paused at `end` with a cursor
+#[derive(Debug, Clone, Copy)]
+struct View {
+  end: u64,            // msec, right edge of charts
+  cursor: Option<u64>, // msec, inspected point
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pause {
+  at: u64,   // msec, time when paused
+  back: u64, // msec, cursor offset from `at`
+}
+
+// Sparkline data from the most detailed tier, newest first (for RightToLeft rendering)
+fn spark_data(series: &Series, scale: f64, view: &View, cursor: Color) -> Vec<SparklineBar> {
+  let points = series.points(0).rev().filter(|x| x.ts <= view.end).collect::<Vec<_>>();
+  let idx = view.cursor.and_then(|ts| points.iter().rposition(|x| x.ts >= ts));
+
+  let bars = points.iter().enumerate().map(|(i, x)| {
+    let bar = SparklineBar::from((x.avg * scale) as u64);
+    if Some(i) == idx {
+      bar.style(Style::new().fg(cursor))
+    } else {
+      bar
+    }
+  });
+
+  bars.collect()
+}
+
+// Chart data for `window` seconds before the view end: x – seconds relative to it, y – scaled
+fn chart_data(series: &Series, window: u64, scale: f64, view: &View) -> Vec<(f64, f64)> {
+  let (end, window_ms) = (view.end, window * 1000);
+  let since = end.saturating_sub(window_ms);
+
+  let points = series.points(series.tier_for(window_ms)).filter(|x| x.ts >= since && x.ts <= end);
+  points.map(|x| ((x.ts as f64 - end as f64) / 1000.0, x.avg * scale)).collect()
+}
+
+// (last, min, avg, max) of chart points, None if there is no data in the window
+fn window_stats(points: &[(f64, f64)]) -> Option<(f64, f64, f64, f64)> {
+  let last = points.last()?.1;
+  let min = points.iter().map(|x| x.1).fold(f64::MAX, f64::min);
+  let max = points.iter().map(|x| x.1).fold(f64::MIN, f64::max);
+  let avg = points.iter().map(|x| x.1).sum::<f64>() / points.len() as f64;
+  Some((last, min, avg, max))
+}
+
+// Chart line: legend name (empty to hide), color and points
+type ChartLine = (String, Color, Vec<(f64, f64)>);
+
+// Lines of one chart, x – seconds relative to the view end
+struct ChartData {
+  window: u64, // seconds
+  max_y: f64,
+  lines: Vec<ChartLine>,
+}
+
+// Zoom stats line: label, series, scale of chart values and their formatter
+type StatsItem<'a> = (&'a str, &'a Series, f64, &'a dyn Fn(f64) -> String);
+
+fn value_at(series: &Series, cursor: u64) -> f64 {
+  series.value_at(cursor).map_or(0.0, |x| x.avg)
 }
 
 #[derive(Debug, Default)]
 struct FreqStore {
-  items: Vec<u64>, // from 0 to 100
+  items: Series, // from 0.0 to 1.0
+  freqs: Series, // MHz
   top_value: u64,
   usage: f64, // from 0.0 to 1.0
+  unavailable: bool,
 }
 
 impl FreqStore {
-  fn push(&mut self, value: u64, usage: f64) {
-    items_add(&mut self.items, (usage * 100.0) as u64);
-    self.top_value = value;
-    self.usage = usage;
+  fn new(tiers: &[TierSpec]) -> Self {
+    Self { items: Series::new(tiers), freqs: Series::new(tiers), ..Default::default() }
+  }
+
+  fn push(&mut self, ts: u64, value: Option<(u32, f32)>) {
+    self.unavailable = value.is_none();
+    let Some((value, usage)) = value else { return };
+
+    self.items.push_at(ts, usage as f64);
+    self.freqs.push_at(ts, value as f64);
+    self.top_value = value as u64;
+    self.usage = usage as f64;
+  }
+
+  // (freq, usage) – latest or at cursor
+  fn values(&self, view: &View) -> (u64, f64) {
+    match view.cursor {
+      None => (self.top_value, self.usage),
+      Some(ts) => (value_at(&self.freqs, ts) as u64, value_at(&self.items, ts)),
+    }
   }
 }
 
 #[derive(Debug, Default)]
 struct PowerStore {
-  items: Vec<u64>,
+  items: Series,
   top_value: f64,
   max_value: f64,
   avg_value: f64,
+  unavailable: bool,
 }
 
 impl PowerStore {
-  fn push(&mut self, value: f64) {
-    items_add(&mut self.items, (value * 1000.0) as u64);
-    self.top_value = value;
-    self.avg_value = self.items.iter().sum::<u64>() as f64 / self.items.len() as f64 / 1000.0;
-    self.max_value = self.items.iter().max().map_or(0, |v| *v) as f64 / 1000.0;
+  fn new(tiers: &[TierSpec]) -> Self {
+    Self { items: Series::new(tiers), ..Default::default() }
+  }
+
+  fn push(&mut self, ts: u64, value: Option<f32>) {
+    self.unavailable = value.is_none();
+    let Some(value) = value else { return };
+
+    self.items.push_at(ts, value as f64);
+    self.top_value = value as f64;
+    self.avg_value = self.items.avg();
+    self.max_value = self.items.max();
+  }
+
+  fn value(&self, view: &View) -> f64 {
+    view.cursor.map_or(self.top_value, |ts| value_at(&self.items, ts))
   }
 }
 
 #[derive(Debug, Default)]
 struct MemoryStore {
-  items: Vec<u64>,
+  items: Series,
+  swap: Series,
   ram_usage: u64,
   ram_total: u64,
   swap_usage: u64,
   swap_total: u64,
   max_ram: u64,
+  unavailable: bool,
 }
 
 impl MemoryStore {
-  fn push(&mut self, value: MemMetrics) {
-    items_add(&mut self.items, value.ram_usage);
+  fn new(tiers: &[TierSpec]) -> Self {
+    Self { items: Series::new(tiers), swap: Series::new(tiers), ..Default::default() }
+  }
+
+  fn push(&mut self, ts: u64, value: Option<MemMetrics>) {
+    self.unavailable = value.is_none();
+    let Some(value) = value else { return };
+
+    self.items.push_at(ts, value.ram_usage as f64);
+    self.swap.push_at(ts, value.swap_usage as f64);
     self.ram_usage = value.ram_usage;
     self.ram_total = value.ram_total;
     self.swap_usage = value.swap_usage;
     self.swap_total = value.swap_total;
-    self.max_ram = self.items.iter().max().map_or(0, |v| *v);
+    self.max_ram = self.items.max() as u64;
+  }
+
+  // (ram_usage, swap_usage) – latest or at cursor
+  fn values(&self, view: &View) -> (u64, u64) {
+    match view.cursor {
+      None => (self.ram_usage, self.swap_usage),
+      Some(ts) => (value_at(&self.items, ts) as u64, value_at(&self.swap, ts) as u64),
+    }
   }
 }
 
-// MARK: Components
+#[derive(Debug, Default)]
+struct TempStore {
+  cpu: Series,
+  gpu: Series,
+  last: Option<TempMetrics>,
+}
 
-fn h_stack(area: Rect) -> (Rect, Rect) {
-  let ha = Layout::default()
-    .direction(Direction::Horizontal)
-    .constraints([Constraint::Fill(1), Constraint::Fill(1)].as_ref())
-    .split(area);
+impl TempStore {
+  fn new(tiers: &[TierSpec]) -> Self {
+    Self { cpu: Series::new(tiers), gpu: Series::new(tiers), last: None }
+  }
+
+  fn push(&mut self, ts: u64, value: Option<TempMetrics>) {
+    // group without valid sensors leaves a gap instead of 0°C
+    if let Some(x) = &value {
+      if let Some(temp) = x.cpu_temp_avg {
+        self.cpu.push_at(ts, temp as f64);
+      }
+      if let Some(temp) = x.gpu_temp_avg {
+        self.gpu.push_at(ts, temp as f64);
+      }
+    }
+    self.last = value;
+  }
 
-  (ha[0], ha[1])
+  // (cpu, gpu) – latest or at cursor, None if unavailable
+  fn values(&self, view: &View) -> (Option<f32>, Option<f32>) {
+    let at = |series: &Series, ts: u64| series.value_at(ts).map(|x| x.avg as f32);
+    match (view.cursor, &self.last) {
+      (Some(ts), _) => (at(&self.cpu, ts), at(&self.gpu, ts)),
+      (None, Some(x)) => (x.cpu_temp_avg, x.gpu_temp_avg),
+      (None, None) => (None, None),
+    }
+  }
+}
+
+// MARK: Components
+
+fn fmt_secs(secs: u64) -> String {
+  match secs {
+    x if x >= 86400 && x % 86400 == 0 => format!("{}d", x / 86400),
+    x if x >= 3600 && x % 3600 == 0 => format!("{}h", x / 3600),
+    x if x >= 60 && x % 60 == 0 => format!("{}m", x / 60),
+    x => format!("{}s", x),
+  }
 }
 
 // MARK: Threads
 
 enum Event {
   Update(Metrics),
+  Error(String), // sampler failure, shown in the status line
   ChangeColor,
   ChangeView,
+  ChangeInterval(bool), // true – slower, false – faster
+  ChangeWindow,
+  ChangeProfile,
+  TogglePause,
+  MoveCursor(bool), // true – back in time, false – forward
+  ToggleHelp,
+  Snapshot,
+  Click(u16, u16),        // column, row
+  Scroll(u16, u16, bool), // true – longer window, false – shorter
+  Escape,
   Tick,
   Quit,
 }
 
-fn handle_key_event(key: &event::KeyEvent, tx: &mpsc::Sender<Event>) -> WithError<()> {
-  match key.code {
-    KeyCode::Char('q') => Ok(tx.send(Event::Quit)?),
-    KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => Ok(tx.send(Event::Quit)?),
-    KeyCode::Char('c') => Ok(tx.send(Event::ChangeColor)?),
-    KeyCode::Char('v') => Ok(tx.send(Event::ChangeView)?),
-    _ => Ok(()),
-  }
+fn handle_key_event(
+  key: &event::KeyEvent,
+  keys: &KeyMap,
+  tx: &mpsc::Sender<Event>,
+) -> WithError<()> {
+  let event = match keys.action(key) {
+    Some(Action::Quit) => Event::Quit,
+    Some(Action::Help) => Event::ToggleHelp,
+    Some(Action::Theme) => Event::ChangeColor,
+    Some(Action::View) => Event::ChangeView,
+    Some(Action::Window) => Event::ChangeWindow,
+    Some(Action::Pause) => Event::TogglePause,
+    Some(Action::CursorBack) => Event::MoveCursor(true),
+    Some(Action::CursorForward) => Event::MoveCursor(false),
+    Some(Action::Slower) => Event::ChangeInterval(true),
+    Some(Action::Faster) => Event::ChangeInterval(false),
+    Some(Action::Snapshot) => Event::Snapshot,
+    Some(Action::Profile) => Event::ChangeProfile,
+    None if key.code == KeyCode::Esc => Event::Escape,
+    None => return Ok(()),
+  };
+
+  Ok(tx.send(event)?)
+}
+
+fn handle_mouse_event(ev: &event::MouseEvent, tx: &mpsc::Sender<Event>) -> WithError<()> {
+  let event = match ev.kind {
+    MouseEventKind::Down(MouseButton::Left) => Event::Click(ev.column, ev.row),
+    MouseEventKind::ScrollUp => Event::Scroll(ev.column, ev.row, false),
+    MouseEventKind::ScrollDown => Event::Scroll(ev.column, ev.row, true),
+    _ => return Ok(()),
+  };
+
+  Ok(tx.send(event)?)
 }
 
-fn run_inputs_thread(tx: mpsc::Sender<Event>, tick: u64) {
+fn run_inputs_thread(
+  tx: mpsc::Sender<Event>,
+  tick: u64,
+  keys: KeyMap,
+  ctrl: mpsc::Receiver<KeyMap>,
+) {
   let tick_rate = Duration::from_millis(tick);
 
   std::thread::spawn(move || {
+    let mut keys = keys;
     let mut last_tick = Instant::now();
 
     loop {
+      // key bindings are replaced when config file is reloaded
+      while let Ok(x) = ctrl.try_recv() {
+        keys = x;
+      }
+
       if event::poll(Duration::from_millis(tick)).unwrap() {
         match event::read().unwrap() {
-          event::Event::Key(key) => handle_key_event(&key, &tx).unwrap(),
+          event::Event::Key(key) => handle_key_event(&key, &keys, &tx).unwrap(),
+          event::Event::Mouse(ev) => handle_mouse_event(&ev, &tx).unwrap(),
           _ => {}
         };
       }
@@ -156,17 +387,58 @@ fn run_inputs_thread(tx: mpsc::Sender<Event>, tick: u64) {
   });
 }
 
-fn run_sampler_thread(tx: mpsc::Sender<Event>, interval: u64) {
-  let interval = interval.max(100).min(10000);
+fn run_sampler_thread(
+  tx: mpsc::Sender<Event>,
+  interval: u64,
+  ctrl: mpsc::Receiver<u64>,
+  sensors: mpsc::Receiver<SensorsConfig>,
+) {
+  let clamp = |x: u64| x.clamp(MIN_INTERVAL, MAX_INTERVAL);
+  let mut interval = clamp(interval);
 
   std::thread::spawn(move || {
-    let mut sampler = Sampler::new().unwrap();
-
-    // Send initial metrics
-    tx.send(Event::Update(sampler.get_metrics(100).unwrap())).unwrap();
+    let mut sampler: Option<Sampler> = None;
+    let mut sensors_cfg = SensorsConfig::default();
+    let mut duration = 100; // initial metrics are sent quickly
 
     loop {
-      tx.send(Event::Update(sampler.get_metrics(interval).unwrap())).unwrap();
+      // new interval applies to the next sample, sampler keeps its previous baseline
+      while let Ok(x) = ctrl.try_recv() {
+        interval = clamp(x);
+      }
+
+      // sensor groups are replaced when config file is reloaded
+      while let Ok(x) = sensors.try_recv() {
+        sensors_cfg = x;
+        if let Some(sampler) = sampler.as_mut() {
+          sampler.set_sensors(&sensors_cfg);
+        }
+      }
+
+      // on failure panels show N/A and the reason, sampler is created again on next round
+      let rs = match sampler.as_mut() {
+        Some(x) => x.get_metrics(duration),
+        None => Sampler::new().and_then(|mut x| {
+          x.set_sensors(&sensors_cfg);
+          sampler.insert(x).get_metrics(duration)
+        }),
+      };
+
+      let sent = match rs {
+        Ok(x) => tx.send(Event::Update(x)),
+        Err(err) => {
+          std::thread::sleep(Duration::from_millis(interval));
+          let _ = tx.send(Event::Update(Metrics::default()));
+          tx.send(Event::Error(format!("Sampler error: {}", err)))
+        }
+      };
+
+      // app is closed
+      if sent.is_err() {
+        break;
+      }
+
+      duration = interval;
     }
   });
 }
@@ -176,16 +448,32 @@ fn run_sampler_thread(tx: mpsc::Sender<Event>, interval: u64) {
 #[derive(Debug, Default)]
 pub struct App {
   cfg: Config,
+  interval: u64,        // msec, current sampling interval
+  pause: Option<Pause>, // history inspection mode
+  theme: Theme,
+  thresholds: Thresholds,
+  keys: KeyMap,
+  help: bool,                             // help popup is shown
+  zoom: Option<LayoutNode>,               // panel maximized by click
+  windows: BTreeMap<PanelKind, u64>,      // chart windows changed by mouse scroll, seconds
+  hits: RefCell<Vec<(Rect, LayoutNode)>>, // panel areas of the last frame, for mouse hit-testing
+  session: Session,
+  summary_file: Option<String>, // JSON copy of the summary printed on exit
+  notice: Option<(String, u64)>, // short message and its expiration time, msec
+  compact: Option<Compact>,     // forced compact mode, otherwise chosen by terminal size
 
   soc: SocInfo,
   mem: MemoryStore,
-  temp: TempMetrics,
+  temp: TempStore,
+  fans: Option<Vec<FanMetrics>>, // latest sample, shown in header
+  cores: Option<Vec<(String, u32, f32)>>, // latest sample, no history per core
 
   cpu_power: PowerStore,
   gpu_power: PowerStore,
   ane_power: PowerStore,
   all_power: PowerStore,
   sys_power: PowerStore,
+  other_power: PowerStore,
 
   ecpu_freq: FreqStore,
   pcpu_freq: FreqStore,
@@ -193,33 +481,114 @@ pub struct App {
 }
 
 impl App {
-  pub fn new() -> WithError<Self> {
+  pub fn new(cfg: Config) -> WithError<Self> {
     let soc = SocInfo::new()?;
-    let cfg = Config::load();
-    Ok(Self { cfg, soc, ..Default::default() })
+    let keys = KeyMap::new(&cfg.keys)?;
+
+    let mut app = Self::with_config(cfg, soc);
+    app.keys = keys;
+    Ok(app)
   }
 
+  fn with_config(cfg: Config, soc: SocInfo) -> Self {
+    let interval = cfg.interval;
+    let tiers = cfg.history.clone();
+    let theme = cfg.get_theme();
+    let thresholds = Thresholds::new(&soc.chip_name, &cfg.thresholds);
+
+    // config problems are also printed to stderr, shown here as they are hidden by the TUI
+    let notice = match cfg.warnings.as_slice() {
+      [] => None,
+      [x] => Some((x.clone(), now_ms() + 10000)),
+      [x, rest @ ..] => Some((format!("{} (+{} more)", x, rest.len()), now_ms() + 10000)),
+    };
+
+    Self {
+      cfg,
+      theme,
+      thresholds,
+      keys: KeyMap::default(),
+      help: false,
+      zoom: None,
+      windows: BTreeMap::new(),
+      hits: RefCell::new(Vec::new()),
+      session: Session::new(now_ms()),
+      summary_file: None,
+      notice,
+      soc,
+      interval,
+      pause: None,
+      compact: None,
+      mem: MemoryStore::new(&tiers),
+      temp: TempStore::new(&tiers),
+      fans: None,
+      cores: None,
+      cpu_power: PowerStore::new(&tiers),
+      gpu_power: PowerStore::new(&tiers),
+      ane_power: PowerStore::new(&tiers),
+      all_power: PowerStore::new(&tiers),
+      sys_power: PowerStore::new(&tiers),
+      other_power: PowerStore::new(&tiers),
+      ecpu_freq: FreqStore::new(&tiers),
+      pcpu_freq: FreqStore::new(&tiers),
+      igpu_freq: FreqStore::new(&tiers),
+    }
+  }
+
+  // All stores get the same timestamp, so points of different series are aligned
   fn update_metrics(&mut self, data: Metrics) {
-    self.cpu_power.push(data.cpu_power as f64);
-    self.gpu_power.push(data.gpu_power as f64);
-    self.ane_power.push(data.ane_power as f64);
-    self.all_power.push(data.all_power as f64);
-    self.sys_power.push(data.sys_power as f64);
-    self.ecpu_freq.push(data.ecpu_usage.0 as u64, data.ecpu_usage.1 as f64);
-    self.pcpu_freq.push(data.pcpu_usage.0 as u64, data.pcpu_usage.1 as f64);
-    self.igpu_freq.push(data.gpu_usage.0 as u64, data.gpu_usage.1 as f64);
-    self.temp = data.temp;
-    self.mem.push(data.memory);
+    let ts = now_ms();
+    self.session.push(ts, &data);
+    self.cpu_power.push(ts, data.cpu_power);
+    self.gpu_power.push(ts, data.gpu_power);
+    self.ane_power.push(ts, data.ane_power);
+    self.all_power.push(ts, data.all_power);
+    self.sys_power.push(ts, data.sys_power);
+    self.other_power.push(ts, data.other_power);
+    self.ecpu_freq.push(ts, data.ecpu_usage);
+    self.pcpu_freq.push(ts, data.pcpu_usage);
+    self.igpu_freq.push(ts, data.gpu_usage);
+    self.temp.push(ts, data.temp);
+    self.mem.push(ts, data.memory);
+    self.fans = data.fans;
+    self.cores = data.cores;
+  }
+
+  fn view(&self) -> View {
+    match self.pause {
+      None => View { end: now_ms(), cursor: None },
+      Some(p) => {
+        // scroll by whole windows, so cursor is always on screen
+        let window = self.cfg.chart_window * 1000;
+        let page = p.back / window * window;
+        View { end: p.at - page, cursor: Some(p.at - p.back) }
+      }
+    }
+  }
+
+  fn move_cursor(&mut self, back: bool) {
+    let step = self.interval.max(self.cfg.chart_window * 1000 / 60);
+    let keep = self.cfg.history.iter().map(|x| x.keep * 1000).max().unwrap_or(0);
+
+    let pause = self.pause.get_or_insert(Pause { at: now_ms(), back: 0 });
+    pause.back = match back {
+      true => (pause.back + step).min(keep),
+      false => pause.back.saturating_sub(step),
+    };
   }
 
   fn title_block<'a>(&self, label_l: &str, label_r: &str) -> Block<'a> {
     let mut block = Block::new()
       .borders(Borders::ALL)
-      .border_type(BorderType::Rounded)
-      .border_style(self.cfg.color)
-      // .title_style(Style::default().gray())
+      .border_style(self.theme.border)
+      .title_style(self.theme.title)
       .padding(Padding::ZERO);
 
+    block = match self.cfg.ascii {
+      true => block.border_set(ASCII_BORDER),
+      false => block.border_type(BorderType::Rounded),
+    };
+
     if label_l.len() > 0 {
       block = block.title_top(Line::from(format!(" {label_l} ")));
     }
@@ -231,172 +600,1667 @@ impl App {
     block
   }
 
-  fn get_power_block<'a>(&self, label: &str, val: &'a PowerStore, temp: f32) -> Sparkline<'a> {
+  fn line_gauge<'a>(&self, label: String, ratio: f64, color: Color) -> LineGauge<'a> {
+    let w = LineGauge::default().filled_style(color).style(self.theme.label).label(label);
+    let w = if self.cfg.ascii { w.line_set(ASCII_LINE) } else { w };
+    w.ratio(ratio.clamp(0.0, 1.0))
+  }
+
+  fn sparkline<'a>(&self) -> Sparkline<'a> {
+    let w = Sparkline::default().direction(RenderDirection::RightToLeft);
+    match self.cfg.ascii {
+      true => w.bar_set(ASCII_BAR),
+      false => w,
+    }
+  }
+
+  fn render_power_block(
+    &self,
+    f: &mut Frame,
+    r: Rect,
+    label: &str,
+    val: &PowerStore,
+    vt: ViewType,
+    kind: PanelKind,
+  ) {
+    if val.unavailable {
+      f.render_widget(self.title_block(&format!("{} N/A", label), ""), r);
+      return;
+    }
+
+    let view = self.view();
+    let units = self.units();
     let label_l = format!(
-      "{} {:.2}W ({:.2}, {:.2})",
+      "{} {} ({}, {})",
       // "{} {:.2}W (avg: {:.2}W, max: {:.2}W)",
       // "{} {:.2}W (~{:.2}W ^{:.2}W)",
       label,
-      val.top_value,
-      val.avg_value,
-      val.max_value
+      units.fmt_power(val.value(&view), 2),
+      units.fmt_power(val.avg_value, 2),
+      units.fmt_power(val.max_value, 2)
     );
 
-    let label_r = if temp > 0.0 { format!("{:.1}°C", temp) } else { "".to_string() };
+    let (cpu_temp, gpu_temp) = self.temp.values(&view);
+    let temp = match kind {
+      PanelKind::CpuPower => cpu_temp,
+      PanelKind::GpuPower => gpu_temp,
+      _ => None,
+    };
 
-    Sparkline::default()
-      .block(self.title_block(label_l.as_str(), label_r.as_str()))
-      .direction(RenderDirection::RightToLeft)
-      .data(&val.items)
-      .style(self.cfg.color)
+    let label_r = temp.map_or(String::new(), |x| units.fmt_temp(x as f64, 1));
+    let (block, color) = self.panel_block(kind, &view, label_l.as_str(), label_r.as_str());
+
+    match vt {
+      ViewType::Chart => {
+        let window = self.window(kind);
+        let points = chart_data(&val.items, window, 1.0, &view);
+        let max_y = points.iter().map(|x| x.1).fold(1.0, f64::max);
+        let data = ChartData { window, max_y, lines: vec![(String::new(), color, points)] };
+        self.render_chart(f, r, block, &data, |x| units.fmt_power(x, 1));
+      }
+      // no gauge for power, there is no upper bound
+      _ => {
+        let w = self
+          .sparkline()
+          .block(block)
+          .data(spark_data(&val.items, 1000.0, &view, self.theme.cursor))
+          .style(color);
+        f.render_widget(w, r);
+      }
+    }
   }
 
-  fn render_freq_block(&self, f: &mut Frame, r: Rect, label: &str, val: &FreqStore) {
-    let label = format!("{} {:3.0}% @ {:4.0} MHz", label, val.usage * 100.0, val.top_value);
-    let block = self.title_block(label.as_str(), "");
+  fn render_temp_block(
+    &self,
+    f: &mut Frame,
+    r: Rect,
+    label: &str,
+    val: &Series,
+    vt: ViewType,
+    kind: PanelKind,
+  ) {
+    let view = self.view();
+    let (cpu_temp, gpu_temp) = self.temp.values(&view);
+    let temp = if kind == PanelKind::GpuTemp { gpu_temp } else { cpu_temp };
+    let Some(temp) = temp else {
+      f.render_widget(self.title_block(&format!("{} N/A", label), ""), r);
+      return;
+    };
+
+    let units = self.units();
+    let (temp_now, temp_max) = (units.fmt_temp(temp as f64, 1), units.fmt_temp(val.max(), 1));
+    let label = format!("{} {} (max {})", label, temp_now, temp_max);
 
-    match self.cfg.view_type {
+    // hottest sensor of the group, latest sample only
+    let group = if kind == PanelKind::GpuTemp { GPU } else { CPU };
+    let hotspot = match (view.cursor, &self.temp.last) {
+      (None, Some(x)) => x.groups.get(group),
+      _ => None,
+    };
+    let label_r = match hotspot {
+      Some(x) => format!("{} {}", x.hotspot, units.fmt_temp(x.max as f64, 1)),
+      None => String::new(),
+    };
+    let (block, color) = self.panel_block(kind, &view, label.as_str(), label_r.as_str());
+
+    match vt {
       ViewType::Sparkline => {
-        let w = Sparkline::default()
+        let w = self
+          .sparkline()
+          .block(block)
+          .data(spark_data(val, 1.0, &view, self.theme.cursor))
+          .max(110)
+          .style(color);
+        f.render_widget(w, r);
+      }
+      ViewType::Gauge => {
+        let w = Gauge::default()
           .block(block)
-          .direction(RenderDirection::RightToLeft)
-          .data(&val.items)
+          .gauge_style(color)
+          .style(color)
+          .label("")
+          .ratio((temp as f64 / 110.0).clamp(0.0, 1.0));
+        f.render_widget(w, r);
+      }
+      ViewType::Chart => {
+        let window = self.window(kind);
+        // conversion to °F has an offset, so points are converted after scaling
+        let points = chart_data(val, window, 1.0, &view);
+        let points = points.into_iter().map(|(x, y)| (x, units.temp(y))).collect();
+        let max_y = units.temp(110.0);
+        let data = ChartData { window, max_y, lines: vec![(String::new(), color, points)] };
+        let label = units.temp_label();
+        self.render_chart(f, r, block, &data, |x| format!("{:.0}{}", x, label));
+      }
+    }
+  }
+
+  fn render_chart<F: Fn(f64) -> String>(
+    &self,
+    f: &mut Frame,
+    r: Rect,
+    block: Block,
+    data: &ChartData,
+    fmt_y: F,
+  ) {
+    let view = self.view();
+    let (window, max_y) = (data.window, data.max_y);
+    let marker = if self.cfg.ascii { symbols::Marker::Dot } else { symbols::Marker::Braille };
+
+    let mut datasets = data
+      .lines
+      .iter()
+      .map(|(name, color, points)| {
+        let ds =
+          Dataset::default().marker(marker).graph_type(GraphType::Line).style(*color).data(points);
+
+        match name.is_empty() {
+          true => ds,
+          false => ds.name(name.clone()),
+        }
+      })
+      .collect::<Vec<_>>();
+
+    // vertical line at cursor position
+    let cursor = match view.cursor {
+      Some(ts) => {
+        let x = (ts as f64 - view.end as f64) / 1000.0;
+        vec![(x, 0.0), (x, max_y)]
+      }
+      None => vec![],
+    };
+
+    if !cursor.is_empty() {
+      let ds = Dataset::default().graph_type(GraphType::Line).data(&cursor);
+      datasets.push(ds.style(self.theme.cursor));
+    }
+
+    // x labels relative to the pause moment (or now)
+    let offset = self.pause.map_or(0, |p| p.at - view.end) / 1000;
+    let ago = |secs: u64| match secs {
+      0 => "now".to_string(),
+      x => format!("-{}", fmt_secs(x)),
+    };
+
+    let x_labels = vec![ago(offset + window), ago(offset + window / 2), ago(offset)];
+    let y_labels = vec![fmt_y(0.0), fmt_y(max_y / 2.0), fmt_y(max_y)];
+
+    let axis = Axis::default().style(self.theme.label);
+    let w = Chart::new(datasets)
+      .block(block)
+      .legend_position(Some(LegendPosition::TopLeft))
+      .x_axis(axis.clone().bounds([-(window as f64), 0.0]).labels(x_labels))
+      .y_axis(axis.bounds([0.0, max_y]).labels(y_labels));
+    f.render_widget(w, r);
+
+    if self.cfg.ascii {
+      ascii_chart(f.buffer_mut(), r);
+    }
+  }
+
+  fn render_freq_block(
+    &self,
+    f: &mut Frame,
+    r: Rect,
+    label: &str,
+    val: &FreqStore,
+    vt: ViewType,
+    kind: PanelKind,
+  ) {
+    if val.unavailable {
+      f.render_widget(self.title_block(&format!("{} N/A", label), ""), r);
+      return;
+    }
+
+    let view = self.view();
+    let (freq, usage) = val.values(&view);
+    let freq = self.units().fmt_freq(freq as f64);
+    let label = format!("{} {:3.0}% @ {}", label, usage * 100.0, freq);
+    let (block, color) = self.panel_block(kind, &view, label.as_str(), "");
+
+    match vt {
+      ViewType::Sparkline => {
+        let w = self
+          .sparkline()
+          .block(block)
+          .data(spark_data(&val.items, 100.0, &view, self.theme.cursor))
           .max(100)
-          .style(self.cfg.color);
+          .style(color);
         f.render_widget(w, r);
       }
       ViewType::Gauge => {
         let w = Gauge::default()
           .block(block)
-          .gauge_style(self.cfg.color)
-          .style(self.cfg.color)
+          .gauge_style(color)
+          .style(color)
           .label("")
-          .ratio(val.usage);
+          .ratio(usage.clamp(0.0, 1.0));
         f.render_widget(w, r);
       }
+      ViewType::Chart => {
+        let window = self.window(kind);
+        let points = chart_data(&val.items, window, 100.0, &view);
+        let data = ChartData { window, max_y: 100.0, lines: vec![(String::new(), color, points)] };
+        self.render_chart(f, r, block, &data, |x| format!("{:.0}%", x));
+      }
     }
   }
 
-  fn render_mem_block(&self, f: &mut Frame, r: Rect, val: &MemoryStore) {
-    let ram_usage_gb = val.ram_usage as f64 / GB as f64;
-    let ram_total_gb = val.ram_total as f64 / GB as f64;
+  fn render_mem_block(
+    &self,
+    f: &mut Frame,
+    r: Rect,
+    val: &MemoryStore,
+    vt: ViewType,
+    kind: PanelKind,
+  ) {
+    if val.unavailable {
+      f.render_widget(self.title_block("RAM N/A", ""), r);
+      return;
+    }
+
+    let view = self.view();
+    let (ram_usage, swap_usage) = val.values(&view);
 
-    let swap_usage_gb = val.swap_usage as f64 / GB as f64;
-    let swap_total_gb = val.swap_total as f64 / GB as f64;
+    let units = self.units();
+    let (size, unit) = (|x: u64| units.size(x as f64), units.size_label());
 
-    let label_l = format!("RAM {:4.2} / {:4.1} GB", ram_usage_gb, ram_total_gb);
-    let label_r = format!("SWAP {:.2} / {:.1} GB", swap_usage_gb, swap_total_gb);
+    let ram_usage_gb = size(ram_usage);
+    let ram_total_gb = size(val.ram_total);
 
-    let block = self.title_block(label_l.as_str(), label_r.as_str());
-    match self.cfg.view_type {
+    let swap_usage_gb = size(swap_usage);
+    let swap_total_gb = size(val.swap_total);
+
+    let label_l = format!("RAM {:4.2} / {:4.1} {}", ram_usage_gb, ram_total_gb, unit);
+    let label_r = format!("SWAP {:.2} / {:.1} {}", swap_usage_gb, swap_total_gb, unit);
+
+    let (block, color) = self.panel_block(kind, &view, label_l.as_str(), label_r.as_str());
+    match vt {
       ViewType::Sparkline => {
-        let w = Sparkline::default()
+        let w = self
+          .sparkline()
           .block(block)
-          .direction(RenderDirection::RightToLeft)
-          .data(&val.items)
+          .data(spark_data(&val.items, 1.0, &view, self.theme.cursor))
           .max(val.ram_total)
-          .style(self.cfg.color);
+          .style(color);
         f.render_widget(w, r);
       }
       ViewType::Gauge => {
         let w = Gauge::default()
           .block(block)
-          .gauge_style(self.cfg.color)
-          .style(self.cfg.color)
+          .gauge_style(color)
+          .style(color)
           .label("")
-          .ratio(zero_div(ram_usage_gb, ram_total_gb));
+          .ratio(zero_div(ram_usage_gb, ram_total_gb).min(1.0));
         f.render_widget(w, r);
       }
+      ViewType::Chart => {
+        let window = self.window(kind);
+        let points = chart_data(&val.items, window, units.size(1.0), &view);
+        let lines = vec![(String::new(), color, points)];
+        let data = ChartData { window, max_y: ram_total_gb, lines };
+        self.render_chart(f, r, block, &data, |x| format!("{:.0}{}", x, unit));
+      }
     }
   }
 
-  fn render(&mut self, f: &mut Frame) {
-    let label_l = format!(
-      "{} ({}E+{}P+{}GPU {}GB)",
-      self.soc.chip_name,
-      self.soc.ecpu_cores,
-      self.soc.pcpu_cores,
-      self.soc.gpu_cores,
-      self.soc.memory_gb,
-    );
+  // Usage of every CPU core as a grid of bars, latest sample also at cursor
+  fn render_cores_block(&self, f: &mut Frame, r: Rect, kind: PanelKind) {
+    let Some(cores) = self.cores.as_ref() else {
+      f.render_widget(self.title_block("Cores N/A", ""), r);
+      return;
+    };
 
-    let rows = Layout::default()
-      .direction(Direction::Vertical)
-      .constraints([Constraint::Fill(2), Constraint::Fill(1)].as_ref())
-      .split(f.area());
+    let view = self.view();
+    let label = if view.cursor.is_some() { "Cores (latest)" } else { "Cores" };
+    let (block, color) = self.panel_block(kind, &view, label, "");
+    let inner = block.inner(r);
+    f.render_widget(block, r);
+
+    let cols = (inner.width / CORE_CELL_WIDTH).max(1) as usize;
+    let rows = cores.chunks(cols).collect::<Vec<_>>();
+    let areas = Layout::vertical(vec![Constraint::Length(1); rows.len()]).split(inner);
+    for (row, area) in rows.iter().zip(areas.iter()) {
+      let cells = Layout::horizontal(vec![Constraint::Fill(1); cols]).spacing(1).split(*area);
+      for ((name, _, usage), cell) in row.iter().zip(cells.iter()) {
+        let label = format!("{} {:3.0}%", name.replace("CPU", ""), usage * 100.0);
+        f.render_widget(self.line_gauge(label, *usage as f64, color), *cell);
+      }
+    }
+  }
 
-    let brand = format!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
-    let block = self.title_block(&label_l, &brand);
-    let iarea = block.inner(rows[0]);
-    f.render_widget(block, rows[0]);
+  // Custom sensor groups (ssd, battery, ...) one per line, latest sample
+  fn render_temp_groups_block(&self, f: &mut Frame, r: Rect, kind: PanelKind) {
+    let groups = self.temp.last.iter().flat_map(|x| x.custom_groups()).collect::<Vec<_>>();
+    if groups.is_empty() {
+      f.render_widget(self.title_block("Sensors N/A", ""), r);
+      return;
+    }
 
-    let iarea = Layout::default()
-      .direction(Direction::Vertical)
-      .constraints([Constraint::Fill(1), Constraint::Fill(1)].as_ref())
-      .split(iarea);
+    let view = self.view();
+    let label = if view.cursor.is_some() { "Sensors (latest)" } else { "Sensors" };
+    let (block, color) = self.panel_block(kind, &view, label, "");
+    let inner = block.inner(r);
+    f.render_widget(block, r);
+
+    let units = self.units();
+    let areas = Layout::vertical(vec![Constraint::Length(1); groups.len()]).split(inner);
+    for ((name, x), area) in groups.iter().zip(areas.iter()) {
+      let (avg, max) = (units.fmt_temp(x.avg as f64, 1), units.fmt_temp(x.max as f64, 1));
+      let label = format!("{} {} (max {} {})", name.to_uppercase(), avg, x.hotspot, max);
+      f.render_widget(self.line_gauge(label, x.avg as f64 / 110.0, color), *area);
+    }
+  }
 
-    // 1st row
-    let (c1, c2) = h_stack(iarea[0]);
-    self.render_freq_block(f, c1, "E-CPU", &self.ecpu_freq);
-    self.render_freq_block(f, c2, "P-CPU", &self.pcpu_freq);
+  // Power panels of a group drawn on one plot (chart view)
+  fn render_power_chart(&self, f: &mut Frame, r: Rect, panels: &[PanelKind]) {
+    let view = self.view();
+    let units = self.units();
+    let (cpu_temp, gpu_temp) = self.temp.values(&view);
+
+    let window = panels.first().map_or(self.cfg.chart_window, |x| self.window(*x));
+    let mut lines = Vec::new();
+    for kind in panels {
+      let (label, val, temp) = match kind {
+        PanelKind::CpuPower => ("CPU", &self.cpu_power, cpu_temp),
+        PanelKind::GpuPower => ("GPU", &self.gpu_power, gpu_temp),
+        PanelKind::AnePower => ("ANE", &self.ane_power, None),
+        PanelKind::AllPower => ("All", &self.all_power, None),
+        PanelKind::SysPower => ("Total", &self.sys_power, None),
+        _ => continue,
+      };
+
+      if val.unavailable {
+        continue;
+      }
 
-    // 2nd row
-    let (c1, c2) = h_stack(iarea[1]);
-    self.render_mem_block(f, c1, &self.mem);
-    self.render_freq_block(f, c2, "GPU", &self.igpu_freq);
+      let power = units.fmt_power(val.value(&view), 2);
+      let name = match temp {
+        Some(temp) => format!("{} {} {}", label, power, units.fmt_temp(temp as f64, 1)),
+        None => format!("{} {}", label, power),
+      };
 
-    // 3rd row
-    let label_l = format!(
-      "Power: {:.2}W (avg {:.2}W, max {:.2}W)",
-      self.all_power.top_value, self.all_power.avg_value, self.all_power.max_value,
-    );
+      let points = chart_data(&val.items, window, 1.0, &view);
+      let level = self.panel_level(*kind, &view);
+      lines.push((name, self.level_color(self.panel_color(*kind), level), points));
+    }
+
+    let max_y = lines.iter().flat_map(|x| x.2.iter().map(|p| p.1)).fold(1.0, f64::max);
+    let data = ChartData { window, max_y, lines };
+    self.render_chart(f, r, Block::new(), &data, |x| units.fmt_power(x, 1));
+  }
 
-    // Show label only if sensor is available
-    let label_r = if self.sys_power.top_value > 0.0 {
+  // Power composition over time: stacked column per point, newest on the right,
+  // last layer is the remainder up to system power
+  fn render_power_stack(&self, f: &mut Frame, r: Rect, kind: PanelKind) {
+    let view = self.view();
+    let power = |x: f64| self.units().fmt_power(x, 2);
+    let glyphs = match self.cfg.ascii {
+      true => ["#", "=", "+", "-", "."],
+      false => ["█", "▓", "▒", "░", "·"],
+    };
+
+    let layers = [
+      ("CPU", &self.cpu_power, self.theme.cpu),
+      ("GPU", &self.gpu_power, self.theme.gpu),
+      ("ANE", &self.ane_power, self.theme.ane),
+      ("Other", &self.other_power, self.theme.other),
+    ];
+
+    // points by sample time, layers missing in a sample count as zero
+    let recent = |x: &PowerStore| -> BTreeMap<u64, f64> {
+      x.items.points(0).filter(|p| p.ts <= view.end).map(|p| (p.ts, p.avg)).collect()
+    };
+
+    let total = recent(&self.sys_power);
+    let values = layers.iter().map(|x| recent(x.1)).collect::<Vec<_>>();
+    let mut stamps =
+      values.iter().chain([&total]).flat_map(|x| x.keys().copied()).collect::<Vec<_>>();
+    stamps.sort_unstable_by(|a, b| b.cmp(a)); // newest first
+    stamps.dedup();
+    let columns = stamps.iter().map(|ts| {
+      let mut col = values.iter().map(|x| x.get(ts).copied().unwrap_or(0.0)).collect::<Vec<_>>();
+      let rest = total.get(ts).copied().unwrap_or(0.0) - col.iter().sum::<f64>();
+      col.push(rest.max(0.0));
+      col
+    });
+
+    // legend with values at view end (or cursor)
+    let mut legend = Vec::new();
+    let mut sum = 0.0;
+    for (i, (label, val, color)) in layers.iter().enumerate() {
+      if val.unavailable {
+        continue;
+      }
+
+      sum += val.value(&view);
+      let text = format!("{} {} {} ", glyphs[i], label, power(val.value(&view)));
+      legend.push(Span::styled(text, *color));
+    }
+
+    let sys_power = !self.sys_power.unavailable && self.sys_power.top_value > 0.0;
+    if sys_power {
+      let rest = (self.sys_power.value(&view) - sum).max(0.0);
+      let text = format!("{} Rest {} ", glyphs[4], power(rest));
+      legend.push(Span::styled(text, self.theme.total));
+    }
+
+    let total = if sys_power { self.sys_power.value(&view) } else { sum };
+    let label = format!("Power {}", power(total));
+    let (block, _) = self.panel_block(kind, &view, &label, "");
+    let block = block.title_top(Line::from(legend).right_aligned());
+
+    let inner = block.inner(r);
+    f.render_widget(block, r);
+    if inner.width == 0 || inner.height == 0 {
+      return;
+    }
+
+    let columns = columns.take(inner.width as usize).collect::<Vec<_>>();
+    let max_y = columns.iter().map(|x| x.iter().sum::<f64>()).fold(1.0, f64::max);
+    let colors =
+      [self.theme.cpu, self.theme.gpu, self.theme.ane, self.theme.other, self.theme.total];
+    let scale = |x: f64| (x / max_y * inner.height as f64).round() as u16;
+
+    let buf = f.buffer_mut();
+    for (i, col) in columns.iter().enumerate() {
+      let x = inner.right() - 1 - i as u16;
+      let mut acc = 0.0;
+
+      for (j, value) in col.iter().enumerate() {
+        let y0 = scale(acc);
+        acc += value;
+        let y1 = scale(acc).min(inner.height);
+
+        for h in y0..y1 {
+          buf[(x, inner.bottom() - 1 - h)].set_symbol(glyphs[j]).set_fg(colors[j]);
+        }
+      }
+    }
+  }
+
+  fn units(&self) -> Units {
+    self.cfg.units
+  }
+
+  fn panel_color(&self, kind: PanelKind) -> Color {
+    match kind {
+      PanelKind::EcpuFreq
+      | PanelKind::PcpuFreq
+      | PanelKind::CpuPower
+      | PanelKind::CpuTemp
+      | PanelKind::CpuCores => self.theme.cpu,
+      PanelKind::GpuFreq | PanelKind::GpuPower | PanelKind::GpuTemp => self.theme.gpu,
+      PanelKind::AnePower => self.theme.ane,
+      PanelKind::Memory => self.theme.memory,
+      PanelKind::AllPower | PanelKind::SysPower | PanelKind::PowerStack => self.theme.total,
+      PanelKind::TempGroups => self.theme.other,
+    }
+  }
+
+  // MARK: Alerts
+
+  // Values of metrics with thresholds, latest or at cursor
+  fn metric_values(&self, view: &View) -> Vec<(Metric, f64)> {
+    let mut items = Vec::new();
+
+    let power = [
+      (Metric::CpuPower, &self.cpu_power),
+      (Metric::GpuPower, &self.gpu_power),
+      (Metric::AnePower, &self.ane_power),
+      (Metric::AllPower, &self.all_power),
+      (Metric::SysPower, &self.sys_power),
+    ];
+    for (metric, val) in power {
+      if !val.unavailable {
+        items.push((metric, val.value(view)));
+      }
+    }
+
+    let (cpu_temp, gpu_temp) = self.temp.values(view);
+    if let Some(temp) = cpu_temp {
+      items.push((Metric::CpuTemp, temp as f64));
+    }
+    if let Some(temp) = gpu_temp {
+      items.push((Metric::GpuTemp, temp as f64));
+    }
+
+    if !self.mem.unavailable {
+      let (ram_usage, swap_usage) = self.mem.values(view);
+      items.push((Metric::Ram, zero_div(ram_usage as f64, self.mem.ram_total as f64) * 100.0));
+      items.push((Metric::Swap, swap_usage as f64 / GIB));
+    }
+
+    items
+  }
+
+  fn panel_level(&self, kind: PanelKind, view: &View) -> Level {
+    let metrics: &[Metric] = match kind {
+      PanelKind::CpuPower => &[Metric::CpuPower],
+      PanelKind::GpuPower => &[Metric::GpuPower],
+      PanelKind::AnePower => &[Metric::AnePower],
+      PanelKind::AllPower => &[Metric::AllPower],
+      PanelKind::SysPower => &[Metric::SysPower],
+      PanelKind::PowerStack => &[Metric::AllPower, Metric::SysPower],
+      PanelKind::CpuTemp => &[Metric::CpuTemp],
+      PanelKind::GpuTemp => &[Metric::GpuTemp],
+      PanelKind::Memory => &[Metric::Ram, Metric::Swap],
+      PanelKind::EcpuFreq | PanelKind::PcpuFreq | PanelKind::GpuFreq => &[],
+      PanelKind::CpuCores | PanelKind::TempGroups => &[],
+    };
+
+    let values = self.metric_values(view).into_iter().filter(|x| metrics.contains(&x.0));
+    values.map(|(m, v)| self.thresholds.level(m, v)).max().unwrap_or_default()
+  }
+
+  fn level_color(&self, color: Color, level: Level) -> Color {
+    match level {
+      Level::Normal => color,
+      Level::Warning => self.theme.warning,
+      Level::Critical => self.theme.critical,
+    }
+  }
+
+  fn level_style(&self, level: Level) -> Style {
+    let style = Style::new().fg(self.level_color(self.theme.title, level));
+    match level {
+      Level::Normal => style,
+      Level::Warning => style.add_modifier(Modifier::BOLD),
+      Level::Critical => style.add_modifier(Modifier::BOLD | Modifier::REVERSED),
+    }
+  }
+
+  // Panel block and data color, both highlighted when metric crosses its threshold
+  fn panel_block<'a>(
+    &self,
+    kind: PanelKind,
+    view: &View,
+    label_l: &str,
+    label_r: &str,
+  ) -> (Block<'a>, Color) {
+    let level = self.panel_level(kind, view);
+    let block = self.title_block(label_l, label_r);
+    let color = self.level_color(self.panel_color(kind), level);
+
+    match level {
+      Level::Normal => (block, color),
+      _ => (block.border_style(color).title_style(self.level_style(level)), color),
+    }
+  }
+
+  // Short list of metrics above thresholds for headers, e.g. "⚠ CPU temp, Swap" ("!" in ascii)
+  fn alerts_label(&self, view: &View) -> Option<(String, Level)> {
+    let alerts = self.thresholds.alerts(&self.metric_values(view));
+    let level = alerts.first()?.1;
+    let names = alerts.iter().map(|x| x.0.label()).collect::<Vec<_>>();
+    let sign = if self.cfg.ascii { "!" } else { "⚠" };
+    Some((format!("{} {}", sign, names.join(", ")), level))
+  }
+
+  fn render_panel(&self, f: &mut Frame, r: Rect, kind: PanelKind, vt: ViewType) {
+    match kind {
+      PanelKind::EcpuFreq => self.render_freq_block(f, r, "E-CPU", &self.ecpu_freq, vt, kind),
+      PanelKind::PcpuFreq => self.render_freq_block(f, r, "P-CPU", &self.pcpu_freq, vt, kind),
+      PanelKind::GpuFreq => self.render_freq_block(f, r, "GPU", &self.igpu_freq, vt, kind),
+      PanelKind::Memory => self.render_mem_block(f, r, &self.mem, vt, kind),
+      PanelKind::CpuPower => self.render_power_block(f, r, "CPU", &self.cpu_power, vt, kind),
+      PanelKind::GpuPower => self.render_power_block(f, r, "GPU", &self.gpu_power, vt, kind),
+      PanelKind::AnePower => self.render_power_block(f, r, "ANE", &self.ane_power, vt, kind),
+      PanelKind::AllPower => self.render_power_block(f, r, "Power", &self.all_power, vt, kind),
+      PanelKind::SysPower => self.render_power_block(f, r, "Total", &self.sys_power, vt, kind),
+      PanelKind::CpuTemp => self.render_temp_block(f, r, "CPU Temp", &self.temp.cpu, vt, kind),
+      PanelKind::GpuTemp => self.render_temp_block(f, r, "GPU Temp", &self.temp.gpu, vt, kind),
+      PanelKind::PowerStack => self.render_power_stack(f, r, kind),
+      PanelKind::CpuCores => self.render_cores_block(f, r, kind),
+      PanelKind::TempGroups => self.render_temp_groups_block(f, r, kind),
+    }
+  }
+
+  fn group_block<'a>(&self, title: GroupTitle) -> Block<'a> {
+    let view = self.view();
+
+    match title {
+      GroupTitle::Soc => {
+        // installed memory is reported in GiB ("16 GB" in system info)
+        let units = self.units();
+        let memory = units.size(self.soc.memory_gb as f64 * GIB);
+        let label_l = format!(
+          "{} ({}E+{}P+{}GPU {:.0}{})",
+          self.soc.chip_name,
+          self.soc.ecpu_cores,
+          self.soc.pcpu_cores,
+          self.soc.gpu_cores,
+          memory,
+          units.size_label(),
+        );
+
+        let mut brand = format!(
+          "{}ms · {} v{}",
+          self.interval,
+          env!("CARGO_PKG_NAME"),
+          env!("CARGO_PKG_VERSION")
+        );
+        if let Some(name) = &self.cfg.profile {
+          brand = format!("{} · {}", name, brand);
+        }
+        if let Some(fans) = self.fans.as_deref().and_then(fmt_fans) {
+          brand = format!("{} · {}", fans, brand);
+        }
+        if let Some(p) = self.pause {
+          brand = format!("PAUSED at -{} · {}", fmt_secs(p.back / 1000), brand);
+        }
+
+        let block = self.title_block(&label_l, &brand);
+        match self.alerts_label(&view) {
+          Some((label, level)) => {
+            let label = Span::styled(format!(" {} ", label), self.level_style(level));
+            block.title_top(Line::from(label).centered())
+          }
+          None => block,
+        }
+      }
+      GroupTitle::Power => {
+        let power = |x: f64| self.units().fmt_power(x, 2);
+        let label_l = match self.all_power.unavailable {
+          true => "Power: N/A".to_string(),
+          false => format!(
+            "Power: {} (avg {}, max {})",
+            power(self.all_power.value(&view)),
+            power(self.all_power.avg_value),
+            power(self.all_power.max_value),
+          ),
+        };
+
+        // Show label only if sensor is available
+        let label_r = if !self.sys_power.unavailable && self.sys_power.top_value > 0.0 {
+          format!(
+            "Total {} ({}, {})",
+            power(self.sys_power.value(&view)),
+            power(self.sys_power.avg_value),
+            power(self.sys_power.max_value)
+          )
+        } else {
+          "".to_string()
+        };
+
+        self.title_block(&label_l, &label_r)
+      }
+    }
+  }
+
+  // Short message, e.g. config errors or saved snapshot, while it's not expired
+  fn notice(&self) -> Option<&str> {
+    self.notice.as_ref().filter(|x| x.1 > now_ms()).map(|x| x.0.as_str())
+  }
+
+  // Bottom line of the screen, drawn over borders of any layout: notice on the left and
+  // controls hint on the right
+  fn render_status(&self, f: &mut Frame, area: Rect, hint: bool) {
+    if area.height == 0 || area.width < 2 {
+      return;
+    }
+
+    let r = Rect::new(area.x + 1, area.bottom() - 1, area.width - 2, 1);
+    if hint {
+      f.render_widget(Line::from(self.controls_hint()).right_aligned(), r);
+    }
+
+    if let Some(text) = self.notice() {
+      f.render_widget(Line::from(Span::styled(format!(" {} ", text), self.theme.title)), r);
+    }
+  }
+
+  fn controls_hint<'a>(&self) -> Span<'a> {
+    let k = |action: Action| self.keys.hint(action);
+    let usage = match self.pause {
+      Some(_) => format!(
+        " Press '{}' to resume, '{}/{}' – move cursor, '{}' – window, '{}' – help ",
+        k(Action::Pause),
+        k(Action::CursorBack),
+        k(Action::CursorForward),
+        k(Action::Window),
+        k(Action::Help),
+      ),
+      None => format!(
+        " Press '{}' to quit, '{}' – help, '{}' – color, '{}' – view, '{}' – pause ",
+        k(Action::Quit),
+        k(Action::Help),
+        k(Action::Theme),
+        k(Action::View),
+        k(Action::Pause),
+      ),
+    };
+
+    Span::styled(usage, self.theme.title)
+  }
+
+  fn render_node(&self, f: &mut Frame, r: Rect, node: &LayoutNode) {
+    let (direction, title, children) = match node {
+      LayoutNode::Panel { panel, view, .. } => {
+        self.hits.borrow_mut().push((r, node.clone()));
+        return self.render_panel(f, r, *panel, view.unwrap_or(self.cfg.view_type));
+      }
+      LayoutNode::Row { title, children, .. } => (Direction::Horizontal, title, children),
+      LayoutNode::Column { title, children, .. } => (Direction::Vertical, title, children),
+    };
+
+    let outer = r;
+    let r = match title {
+      Some(title) => {
+        let block = self.group_block(*title);
+        let iarea = block.inner(r);
+        f.render_widget(block, r);
+        iarea
+      }
+      None => r,
+    };
+
+    // Chart view draws power group on one plot, when all its panels are in chart view
+    let panels = children.iter().filter_map(|x| match x {
+      LayoutNode::Panel { panel, view, .. } => Some((*panel, view.unwrap_or(self.cfg.view_type))),
+      _ => None,
+    });
+    let panels = panels.collect::<Vec<_>>();
+    let chart = !panels.is_empty() && panels.iter().all(|x| x.1 == ViewType::Chart);
+    if *title == Some(GroupTitle::Power) && chart {
+      self.hits.borrow_mut().push((outer, node.clone()));
+      return self.render_power_chart(f, r, &panels.iter().map(|x| x.0).collect::<Vec<_>>());
+    }
+
+    let constraints = children.iter().map(|x| Constraint::Fill(x.size()));
+    let areas = Layout::default().direction(direction).constraints(constraints).split(r);
+    for (child, area) in children.iter().zip(areas.iter()) {
+      self.render_node(f, *area, child);
+    }
+  }
+
+  fn compact_mode(&self, area: Rect) -> Option<Compact> {
+    if self.compact.is_some() {
+      return self.compact;
+    }
+
+    match area {
+      a if a.height <= LINE_MAX_HEIGHT => Some(Compact::Line),
+      a if a.height <= MINI_MAX_HEIGHT => Some(Compact::Mini),
+      a if a.width <= STACK_MAX_WIDTH => Some(Compact::Stack),
+      _ => None,
+    }
+  }
+
+  fn summary_line(&self) -> String {
+    let view = self.view();
+    let units = self.units();
+    let pct = |x: &FreqStore| match x.unavailable {
+      true => "-".to_string(),
+      false => format!("{:.0}%", x.values(&view).1 * 100.0),
+    };
+    let watts = |x: &PowerStore| match x.unavailable {
+      true => "-".to_string(),
+      false => units.fmt_power(x.value(&view), 1),
+    };
+
+    let mut items = vec![
+      format!("E {} P {} GPU {}", pct(&self.ecpu_freq), pct(&self.pcpu_freq), pct(&self.igpu_freq)),
       format!(
-        "Total {:.2}W ({:.2}, {:.2})",
-        self.sys_power.top_value, self.sys_power.avg_value, self.sys_power.max_value
-      )
-    } else {
-      "".to_string()
+        "CPU {} GPU {} ANE {}",
+        watts(&self.cpu_power),
+        watts(&self.gpu_power),
+        watts(&self.ane_power)
+      ),
+    ];
+
+    if !self.mem.unavailable {
+      let ram = units.size(self.mem.values(&view).0 as f64);
+      let total = units.size(self.mem.ram_total as f64);
+      items.push(format!("RAM {:.1}/{:.0}{}", ram, total, units.size_label()));
+    }
+
+    if self.temp.last.is_some() {
+      let (cpu_temp, gpu_temp) = self.temp.values(&view);
+      let temp = |x: Option<f32>| x.map_or("-".to_string(), |x| units.fmt_temp(x as f64, 0));
+      items.push(format!("{}/{}", temp(cpu_temp), temp(gpu_temp)));
+
+      // custom sensor groups, latest values
+      let groups = self.temp.last.iter().flat_map(|x| x.custom_groups());
+      for (name, x) in groups {
+        items.push(format!("{} {}", name.to_uppercase(), units.fmt_temp(x.avg as f64, 0)));
+      }
+    }
+
+    if let Some((label, _)) = self.alerts_label(&view) {
+      items.push(label);
+    }
+
+    if self.pause.is_some() {
+      items.push("PAUSED".to_string());
+    }
+
+    if let Some(text) = self.notice() {
+      items.push(text.to_string());
+    }
+
+    items.join(" · ")
+  }
+
+  fn summary_power(&self, view: &View) -> String {
+    let mut label = match self.all_power.unavailable {
+      true => "N/A".to_string(),
+      false => self.units().fmt_power(self.all_power.value(view), 2),
+    };
+
+    if !self.sys_power.unavailable && self.sys_power.top_value > 0.0 {
+      let total = self.units().fmt_power(self.sys_power.value(view), 2);
+      label = format!("{} (Total {})", label, total);
+    }
+
+    label
+  }
+
+  fn render_line(&self, f: &mut Frame, r: Rect) {
+    let level = self.alerts_label(&self.view()).map_or(Level::Normal, |x| x.1);
+    let style = match level {
+      Level::Normal => Style::new().fg(self.theme.label),
+      _ => self.level_style(level),
     };
 
-    let block = self.title_block(&label_l, &label_r);
-    let usage = " Press 'q' to quit, 'c' – color, 'v' – view ";
-    let block = block.title_bottom(Line::from(usage).right_aligned());
-    let iarea = block.inner(rows[1]);
-    f.render_widget(block, rows[1]);
+    let w = Paragraph::new(self.summary_line()).style(style);
+    f.render_widget(w, r);
+  }
+
+  fn render_mini(&self, f: &mut Frame, r: Rect) {
+    let view = self.view();
+    let rows = Layout::vertical([Constraint::Length(1); 3]).split(r);
+
+    let mut header = format!("{} · Power {}", self.soc.chip_name, self.summary_power(&view));
+    if self.pause.is_some() {
+      header = format!("{} · PAUSED", header);
+    }
+    if let Some(text) = self.notice() {
+      header = format!("{} · {}", header, text);
+    }
+
+    let mut header = vec![Span::styled(header, self.theme.title)];
+    if let Some((label, level)) = self.alerts_label(&view) {
+      header.push(Span::raw(" "));
+      header.push(Span::styled(label, self.level_style(level)));
+    }
+    f.render_widget(Paragraph::new(Line::from(header)), rows[0]);
+
+    let gauge = |label, ratio, color| self.line_gauge(label, ratio, color);
+
+    let cols = Layout::horizontal([Constraint::Fill(1); 3]).spacing(1).split(rows[1]);
+    let items = [
+      ("E", &self.ecpu_freq, self.theme.cpu),
+      ("P", &self.pcpu_freq, self.theme.cpu),
+      ("GPU", &self.igpu_freq, self.theme.gpu),
+    ];
+    for ((label, val, color), area) in items.into_iter().zip(cols.iter()) {
+      let usage = if val.unavailable { 0.0 } else { val.values(&view).1 };
+      let label = format!("{} {:3.0}%", label, usage * 100.0);
+      f.render_widget(gauge(label, usage, color), *area);
+    }
+
+    let cols = Layout::horizontal([Constraint::Fill(1); 2]).spacing(1).split(rows[2]);
+    if !self.mem.unavailable {
+      let (ram_usage, swap_usage) = self.mem.values(&view);
+      let (size, unit) = (|x: u64| self.units().size(x as f64), self.units().size_label());
+      let label = format!("RAM {:.1}{} S {:.1}{}", size(ram_usage), unit, size(swap_usage), unit);
+      let ratio = zero_div(ram_usage as f64, self.mem.ram_total as f64);
+      let color = self.level_color(self.theme.memory, self.panel_level(PanelKind::Memory, &view));
+      f.render_widget(gauge(label, ratio, color), cols[0]);
+    }
+
+    if self.temp.last.is_some() {
+      let (cpu_temp, gpu_temp) = self.temp.values(&view);
+      let temp = |x: Option<f32>| x.map_or("-".to_string(), |x| self.units().fmt_temp(x as f64, 0));
+      let label = format!("CPU {} GPU {}", temp(cpu_temp), temp(gpu_temp));
+      f.render_widget(Paragraph::new(label).style(self.theme.label), cols[1]);
+    }
+  }
+
+  // All panels of the layout in one column, for narrow panes
+  fn render_stack(&self, f: &mut Frame, r: Rect) {
+    let panels = self.cfg.layout.panels().into_iter();
+    let children = panels.map(|(panel, view)| LayoutNode::Panel { size: 1, panel, view }).collect();
+    let node = LayoutNode::Column { size: 1, title: Some(GroupTitle::Soc), children };
+    self.render_node(f, r, &node);
+  }
+
+  // MARK: Mouse
+
+  fn window(&self, kind: PanelKind) -> u64 {
+    self.windows.get(&kind).copied().unwrap_or(self.cfg.chart_window)
+  }
+
+  fn node_at(&self, x: u16, y: u16) -> Option<LayoutNode> {
+    let hits = self.hits.borrow();
+    hits.iter().find(|h| h.0.contains(Position::new(x, y))).map(|h| h.1.clone())
+  }
+
+  fn click(&mut self, x: u16, y: u16) {
+    self.zoom = match self.zoom {
+      Some(_) => None,
+      None => self.node_at(x, y),
+    };
+  }
+
+  // Zoomed panel has focus, otherwise one under the mouse
+  fn scroll(&mut self, x: u16, y: u16, longer: bool) {
+    let Some(node) = self.zoom.clone().or_else(|| self.node_at(x, y)) else { return };
+    for (kind, _) in node.panels() {
+      let window = Config::next_window(self.window(kind), longer);
+      self.windows.insert(kind, window);
+    }
+  }
+
+  // Stats of zoomed panels over their chart window: (label, now, min, avg, max)
+  fn zoom_stats(&self, node: &LayoutNode) -> Vec<String> {
+    let view = self.view();
+    let units = self.units();
+    let (freq_unit, freq_prec) = units.freq_label();
+    let (freq, size) = (units.freq(1.0), units.size(1.0));
+    let mut lines = Vec::new();
+
+    // formatters of scaled chart values
+    let pct = |x: f64| format!("{:.0}%", x);
+    let mhz = |x: f64| format!("{:.p$} {}", x, freq_unit, p = freq_prec);
+    let gib = |x: f64| format!("{:.2} {}", x, units.size_label());
+    let watts = |x: f64| units.fmt_power(x, 2);
+    let temp = |x: f64| units.fmt_temp(x, 1);
+
+    for (kind, _) in node.panels() {
+      let items: Vec<StatsItem> = match kind {
+        PanelKind::EcpuFreq => vec![
+          ("E-CPU usage", &self.ecpu_freq.items, 100.0, &pct),
+          ("E-CPU freq", &self.ecpu_freq.freqs, freq, &mhz),
+        ],
+        PanelKind::PcpuFreq => vec![
+          ("P-CPU usage", &self.pcpu_freq.items, 100.0, &pct),
+          ("P-CPU freq", &self.pcpu_freq.freqs, freq, &mhz),
+        ],
+        PanelKind::GpuFreq => vec![
+          ("GPU usage", &self.igpu_freq.items, 100.0, &pct),
+          ("GPU freq", &self.igpu_freq.freqs, freq, &mhz),
+        ],
+        PanelKind::Memory => {
+          vec![("RAM", &self.mem.items, size, &gib), ("Swap", &self.mem.swap, size, &gib)]
+        }
+        PanelKind::CpuPower => vec![("CPU power", &self.cpu_power.items, 1.0, &watts)],
+        PanelKind::GpuPower => vec![("GPU power", &self.gpu_power.items, 1.0, &watts)],
+        PanelKind::AnePower => vec![("ANE power", &self.ane_power.items, 1.0, &watts)],
+        PanelKind::AllPower => vec![("Power", &self.all_power.items, 1.0, &watts)],
+        PanelKind::SysPower => vec![("Total power", &self.sys_power.items, 1.0, &watts)],
+        // °C are converted by the formatter, conversion to °F has an offset
+        PanelKind::CpuTemp => vec![("CPU temp", &self.temp.cpu, 1.0, &temp)],
+        PanelKind::GpuTemp => vec![("GPU temp", &self.temp.gpu, 1.0, &temp)],
+        PanelKind::PowerStack => vec![
+          ("CPU power", &self.cpu_power.items, 1.0, &watts),
+          ("GPU power", &self.gpu_power.items, 1.0, &watts),
+          ("ANE power", &self.ane_power.items, 1.0, &watts),
+          ("Other power", &self.other_power.items, 1.0, &watts),
+          ("Total power", &self.sys_power.items, 1.0, &watts),
+        ],
+        PanelKind::CpuCores | PanelKind::TempGroups => vec![],
+      };
+
+      for (label, series, scale, fmt) in items {
+        let points = chart_data(series, self.window(kind), scale, &view);
+        lines.push(match window_stats(&points) {
+          Some((last, min, avg, max)) => format!(
+            "{:<12} now {:>10} · min {:>10} · avg {:>10} · max {:>10}",
+            label,
+            fmt(last),
+            fmt(min),
+            fmt(avg),
+            fmt(max)
+          ),
+          None => format!("{:<12} N/A", label),
+        });
+      }
+    }
+
+    lines
+  }
+
+  // Clicked panel on the full screen with its stats below
+  fn render_zoom(&self, f: &mut Frame, area: Rect, node: &LayoutNode) {
+    let stats = self.zoom_stats(node);
+    let rows = Layout::vertical([Constraint::Fill(1), Constraint::Length(stats.len() as u16 + 2)])
+      .split(area);
+
+    self.render_node(f, rows[0], node);
+
+    let window = node.panels().first().map_or(self.cfg.chart_window, |x| self.window(x.0));
+    let label = format!("Last {}", fmt_secs(window));
+    let hint = " Click or Esc – back, scroll – change window ";
+    let block = self.title_block(&label, "").title_bottom(Line::from(hint).right_aligned());
+    let lines = stats.into_iter().map(Line::from).collect::<Vec<_>>();
+    f.render_widget(Paragraph::new(lines).block(block).style(self.theme.label), rows[1]);
+  }
+
+  // Popup with all actions and their keys
+  fn render_help(&self, f: &mut Frame, area: Rect) {
+    let items = Action::ALL.iter().map(|action| {
+      let keys = self.keys.keys(*action).iter().map(|x| x.to_string()).collect::<Vec<_>>();
+      (keys.join(" / "), action.description())
+    });
+    let mut items = items.collect::<Vec<_>>();
+    items.push(("click".to_string(), "Zoom panel / restore layout"));
+    items.push(("scroll".to_string(), "Change chart window of panel"));
+
+    let keys_width = items.iter().map(|x| x.0.chars().count()).max().unwrap_or(0);
+    let lines = items.iter().map(|(keys, text)| {
+      Line::from(vec![
+        Span::styled(format!(" {:>w$} ", keys, w = keys_width), self.theme.title),
+        Span::styled(format!(" {}", text), self.theme.label),
+      ])
+    });
+
+    let width = items.iter().map(|x| keys_width + x.1.chars().count() + 5).max().unwrap_or(0);
+    let width = (width as u16 + 2).min(area.width);
+    let height = (items.len() as u16 + 2).min(area.height);
+    let r = Rect {
+      x: area.x + (area.width - width) / 2,
+      y: area.y + (area.height - height) / 2,
+      width,
+      height,
+    };
+
+    let hint = format!(" '{}' / Esc – close ", self.keys.hint(Action::Help));
+    let block = self.title_block("Help", "").title_bottom(Line::from(hint).right_aligned());
+    let w = Paragraph::new(lines.collect::<Vec<_>>())
+      .block(block)
+      .style(Style::new().bg(self.theme.background));
+
+    f.render_widget(Clear, r);
+    f.render_widget(w, r);
+  }
+
+  fn render(&mut self, f: &mut Frame) {
+    let area = f.area();
+    f.render_widget(Block::new().style(Style::new().bg(self.theme.background)), area);
+    self.hits.borrow_mut().clear();
+
+    if let Some(node) = &self.zoom {
+      self.render_zoom(f, area, node);
+      self.render_status(f, area, false);
+      if self.help {
+        self.render_help(f, area);
+      }
+      return;
+    }
+
+    match self.compact_mode(area) {
+      Some(Compact::Line) => self.render_line(f, area),
+      Some(Compact::Mini) => self.render_mini(f, area),
+      Some(Compact::Stack) => {
+        self.render_stack(f, area);
+        self.render_status(f, area, false);
+      }
+      None => {
+        self.render_node(f, area, &self.cfg.layout);
+        self.render_status(f, area, true);
+      }
+    }
+
+    if self.help {
+      self.render_help(f, area);
+    }
+  }
+
+  pub fn set_compact(&mut self, compact: Option<Compact>) {
+    self.compact = compact;
+  }
+
+  // Saves rendered frame to the current directory, returns file name
+  fn save_snapshot(&self, buf: &Buffer) -> WithError<String> {
+    let format = self.cfg.snapshot_format;
+    let path = format!("macmon-{}.{}", now_ms() / 1000, format.extension());
+    std::fs::write(&path, snapshot::export(buf, format))?;
+    Ok(path)
+  }
+
+  // Renders one frame at the terminal size, used by `--snapshot`
+  pub fn snapshot(&mut self, format: Format) -> WithError<String> {
+    let mut sampler = Sampler::new()?;
+    sampler.set_sensors(&self.cfg.sensors);
+    self.update_metrics(sampler.get_metrics(self.interval)?);
+
+    let (width, height) = terminal::size().unwrap_or((120, 40));
+    let mut term = Terminal::new(backend::TestBackend::new(width, height))?;
+    let frame = term.draw(|f| self.render(f))?;
+    Ok(snapshot::export(frame.buffer, format))
+  }
+
+  // Updates state derived from config after its values were replaced
+  fn config_changed(&mut self) {
+    self.theme = self.cfg.get_theme();
+    self.thresholds = Thresholds::new(&self.soc.chip_name, &self.cfg.thresholds);
+    self.interval = self.cfg.interval;
+    self.zoom = None;
+    self.windows.clear();
+  }
 
-    let ha = Layout::default()
-      .direction(Direction::Horizontal)
-      .constraints([Constraint::Fill(1), Constraint::Fill(1), Constraint::Fill(1)].as_ref())
-      .split(iarea);
+  fn next_profile(&mut self) {
+    self.cfg.next_profile();
+    self.config_changed();
 
-    f.render_widget(self.get_power_block("CPU", &self.cpu_power, self.temp.cpu_temp_avg), ha[0]);
-    f.render_widget(self.get_power_block("GPU", &self.gpu_power, self.temp.gpu_temp_avg), ha[1]);
-    f.render_widget(self.get_power_block("ANE", &self.ane_power, 0.0), ha[2]);
+    let name = self.cfg.profile.as_deref().unwrap_or("default");
+    self.notice = Some((format!("Profile: {}", name), now_ms() + 3000));
   }
 
-  pub fn run_loop(&mut self, interval: u64) -> WithError<()> {
+  // Applies config file changes made while running, history is kept. True if anything changed,
+  // errors stay in the status line until the file is fixed
+  fn reload_config(&mut self) -> bool {
+    let Some(rs) = self.cfg.reload() else { return false };
+
+    let rs = rs.and_then(|_| KeyMap::new(&self.cfg.keys));
+    let notice = match rs {
+      Ok(keys) => {
+        self.keys = keys;
+        self.config_changed();
+
+        let msg = self.cfg.warnings.first().cloned();
+        (msg.unwrap_or("Config reloaded".to_string()), now_ms() + 3000)
+      }
+      Err(err) => (err, u64::MAX),
+    };
+
+    self.notice = Some(notice);
+    true
+  }
+
+  pub fn set_summary_file(&mut self, path: Option<String>) {
+    self.summary_file = path;
+  }
+
+  pub fn run_loop(&mut self) -> WithError<()> {
     let (tx, rx) = mpsc::channel::<Event>();
-    run_inputs_thread(tx.clone(), 250);
-    run_sampler_thread(tx.clone(), interval);
+    let (ctrl_tx, ctrl_rx) = mpsc::channel::<u64>();
+    let (keys_tx, keys_rx) = mpsc::channel::<KeyMap>();
+    let (sensors_tx, sensors_rx) = mpsc::channel::<SensorsConfig>();
+    sensors_tx.send(self.cfg.sensors.clone())?;
+    run_inputs_thread(tx.clone(), 250, self.keys.clone(), keys_rx);
+    run_sampler_thread(tx.clone(), self.interval, ctrl_rx, sensors_rx);
 
     let mut term = enter_term();
+    let guard = TermGuard;
+    let mut redraw = true;
 
     loop {
-      term.draw(|f| self.render(f)).unwrap();
+      if redraw {
+        term.draw(|f| self.render(f)).unwrap();
+      }
 
-      match rx.recv()? {
+      let event = rx.recv()?;
+
+      // while paused metrics are still recorded, but screen changes only on user input
+      redraw = self.pause.is_none() || !matches!(event, Event::Update(_) | Event::Tick);
+
+      match event {
         Event::Quit => break,
         Event::Update(data) => self.update_metrics(data),
-        Event::ChangeColor => self.cfg.next_color(),
+        // kept until the next attempt of the sampler
+        Event::Error(text) => self.notice = Some((text, now_ms() + self.interval + 3000)),
+        Event::ChangeColor => {
+          self.cfg.next_theme();
+          self.theme = self.cfg.get_theme();
+        }
         Event::ChangeView => self.cfg.next_view_type(),
-        _ => {}
+        Event::ChangeWindow => {
+          self.cfg.next_chart_window();
+          self.windows.clear();
+        }
+        Event::ChangeInterval(slower) => {
+          self.interval = Config::next_interval(self.interval, slower);
+          self.cfg.set_interval(self.interval);
+          ctrl_tx.send(self.interval)?;
+        }
+        Event::ChangeProfile => {
+          self.next_profile();
+          ctrl_tx.send(self.interval)?;
+        }
+        Event::TogglePause => {
+          self.pause = match self.pause {
+            Some(_) => None,
+            None => Some(Pause { at: now_ms(), back: 0 }),
+          };
+        }
+        Event::MoveCursor(back) => self.move_cursor(back),
+        Event::ToggleHelp => self.help = !self.help,
+        Event::Snapshot => {
+          let buf = term.draw(|f| self.render(f))?.buffer.clone();
+          let text = match self.save_snapshot(&buf) {
+            Ok(path) => format!("Saved {}", path),
+            Err(err) => format!("Snapshot failed: {}", err),
+          };
+          self.notice = Some((text, now_ms() + 3000));
+        }
+        Event::Click(x, y) => self.click(x, y),
+        Event::Scroll(x, y, longer) => self.scroll(x, y, longer),
+        Event::Escape => match self.help {
+          true => self.help = false,
+          false => self.zoom = None,
+        },
+        Event::Tick => {
+          if self.reload_config() {
+            ctrl_tx.send(self.interval)?;
+            keys_tx.send(self.keys.clone())?;
+            sensors_tx.send(self.cfg.sensors.clone())?;
+            redraw = true;
+          }
+        }
       }
     }
 
-    leave_term();
+    drop(guard);
+
+    let summary = self.session.summary(now_ms(), self.cfg.units);
+    print!("{}", summary);
+    if let Some(path) = &self.summary_file {
+      summary.save(path)?;
+    }
+
     Ok(())
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::metrics::TempGroup;
+  use crate::units::{FreqUnit, PowerUnit, SizeUnit, TempUnit};
+  use ratatui::backend::TestBackend;
+
+  fn test_app() -> App {
+    let soc = SocInfo {
+      chip_name: "Apple M2".to_string(),
+      ecpu_cores: 4,
+      pcpu_cores: 4,
+      gpu_cores: 10,
+      memory_gb: 16,
+      ..Default::default()
+    };
+
+    let mut app = App::with_config(Config::default(), soc);
+    app.update_metrics(Metrics {
+      temp: Some(TempMetrics {
+        cpu_temp_avg: Some(45.0),
+        gpu_temp_avg: Some(40.0),
+        ..Default::default()
+      }),
+      memory: Some(MemMetrics {
+        ram_total: 16 * GIB as u64,
+        ram_usage: 8 * GIB as u64,
+        swap_total: 2 * GIB as u64,
+        swap_usage: GIB as u64,
+      }),
+      ecpu_usage: Some((1000, 0.25)),
+      pcpu_usage: Some((2000, 0.5)),
+      gpu_usage: Some((500, 0.1)),
+      cores: Some(vec![("ECPU0".to_string(), 1000, 0.25), ("PCPU0".to_string(), 2000, 0.5)]),
+      cpu_power: Some(1.5),
+      gpu_power: Some(0.5),
+      ane_power: Some(0.0),
+      all_power: Some(2.0),
+      sys_power: Some(5.0),
+      other_power: Some(0.5),
+      fans: None,
+    });
+    app
+  }
+
+  fn render(app: &mut App, width: u16, height: u16) -> Vec<String> {
+    let mut term = Terminal::new(TestBackend::new(width, height)).unwrap();
+    term.draw(|f| app.render(f)).unwrap();
+
+    let buf = term.backend().buffer();
+    let lines = buf.content.chunks(width as usize);
+    lines.map(|x| x.iter().map(|c| c.symbol()).collect::<String>()).collect()
+  }
+
+  #[test]
+  fn test_compact_mode_by_size() {
+    let mut app = test_app();
+    assert_eq!(app.compact_mode(Rect::new(0, 0, 120, 1)), Some(Compact::Line));
+    assert_eq!(app.compact_mode(Rect::new(0, 0, 120, 3)), Some(Compact::Mini));
+    assert_eq!(app.compact_mode(Rect::new(0, 0, 40, 40)), Some(Compact::Stack));
+    assert_eq!(app.compact_mode(Rect::new(0, 0, 120, 40)), None);
+
+    app.set_compact(Some(Compact::Line));
+    assert_eq!(app.compact_mode(Rect::new(0, 0, 120, 40)), Some(Compact::Line));
+  }
+
+  #[test]
+  fn test_render_line() {
+    let mut app = test_app();
+    let lines = render(&mut app, 100, 1);
+
+    let line = "E 25% P 50% GPU 10% · CPU 1.5W GPU 0.5W ANE 0.0W · RAM 8.0/16GiB · 45°C/40°C";
+    assert_eq!(lines, vec![format!("{:100}", line)]);
+  }
+
+  #[test]
+  fn test_render_units() {
+    let mut app = test_app();
+    app.cfg.units = Units {
+      temp: TempUnit::Fahrenheit,
+      size: SizeUnit::Gb,
+      freq: FreqUnit::Ghz,
+      power: PowerUnit::Auto,
+    };
+
+    let lines = render(&mut app, 100, 1);
+    assert!(lines[0].contains("GPU 500mW ANE 0mW · RAM 8.6/17GB · 113°F/104°F"));
+
+    let lines = render(&mut app, 120, 30);
+    assert!(lines.iter().any(|x| x.contains("E-CPU  25% @ 1.00 GHz")));
+    assert!(lines.iter().any(|x| x.contains("RAM 8.59 / 17.2 GB")));
+    assert!(lines.iter().any(|x| x.contains("Apple M2 (4E+4P+10GPU 17GB)")));
+    assert!(lines.iter().any(|x| x.contains("CPU 1.50W") && x.contains("113.0°F")));
+  }
+
+  #[test]
+  fn test_render_hotspot() {
+    let mut app = test_app();
+    app.cfg.layout = LayoutNode::Panel { size: 1, panel: PanelKind::CpuTemp, view: None };
+
+    let group = |avg: f32, max: f32, hotspot: &str| TempGroup {
+      min: 40.0,
+      avg,
+      max,
+      hotspot: hotspot.to_string(),
+      count: 4,
+    };
+    let groups = BTreeMap::from([
+      (CPU.to_string(), group(46.0, 62.0, "Tp05")),
+      ("ssd".to_string(), group(35.0, 36.0, "NAND CH0 temp")),
+    ]);
+    app.update_metrics(Metrics {
+      temp: Some(TempMetrics { cpu_temp_avg: Some(46.0), gpu_temp_avg: Some(40.0), groups }),
+      ..Default::default()
+    });
+
+    let lines = render(&mut app, 100, 10);
+    assert!(lines.iter().any(|x| x.contains("CPU Temp 46.0°C") && x.contains("Tp05 62.0°C")));
+
+    let lines = render(&mut app, 120, 1);
+    assert!(lines[0].contains("46°C/40°C · SSD 35°C"));
+  }
+
+  #[test]
+  fn test_temp_group_rejected() {
+    let mut app = test_app();
+    app.cfg.layout = LayoutNode::Panel { size: 1, panel: PanelKind::GpuTemp, view: None };
+
+    // gpu sensors rejected: no point at 0°C, panel and status line show it as unavailable
+    let temp = TempMetrics { cpu_temp_avg: Some(46.0), gpu_temp_avg: None, ..Default::default() };
+    app.update_metrics(Metrics { temp: Some(temp), ..Default::default() });
+    assert!(app.temp.gpu.points(0).all(|x| x.avg == 40.0));
+
+    let lines = render(&mut app, 100, 10);
+    assert!(lines[0].contains("GPU Temp N/A"));
+    let lines = render(&mut app, 120, 1);
+    assert!(lines[0].contains("46°C/-"), "{}", lines[0]);
+  }
+
+  #[test]
+  fn test_render_fans() {
+    let mut app = test_app();
+    let lines = render(&mut app, 120, 30);
+    assert!(!lines[0].contains("rpm"));
+
+    let fan = |actual: f32| FanMetrics { actual, min: 1200.0, max: 5779.0, target: None };
+    app
+      .update_metrics(Metrics { fans: Some(vec![fan(1850.0), fan(1902.4)]), ..Default::default() });
+    let lines = render(&mut app, 120, 30);
+    assert!(lines[0].contains("Fans 1850/1902rpm · 1000ms · macmon"));
+  }
+
+  #[test]
+  fn test_render_help() {
+    let mut app = test_app();
+    app.help = true;
+
+    let lines = render(&mut app, 120, 30);
+    assert!(lines.iter().any(|x| x.contains("Help")));
+    assert!(lines.iter().any(|x| x.contains("p / space") && x.contains("Pause / resume display")));
+    assert!(lines.iter().any(|x| x.contains("← ") && x.contains("Move cursor back in history")));
+  }
+
+  #[test]
+  fn test_mouse_zoom() {
+    let mut app = test_app();
+    render(&mut app, 120, 30);
+
+    // second row is inside of E-CPU panel
+    app.click(10, 2);
+    assert!(matches!(app.zoom, Some(LayoutNode::Panel { panel: PanelKind::EcpuFreq, .. })));
+
+    let lines = render(&mut app, 120, 30);
+    assert!(lines[0].contains("E-CPU  25% @ 1000 MHz"));
+    assert!(lines.iter().any(|x| x.contains("Last 1m")));
+    assert!(lines.iter().any(|x| x.contains("E-CPU usage") && x.contains("max        25%")));
+
+    app.scroll(10, 2, true);
+    assert_eq!(app.window(PanelKind::EcpuFreq), 300);
+    assert_eq!(app.window(PanelKind::PcpuFreq), 60);
+
+    // longer windows than the chart_window options read the coarse tiers
+    for _ in 0..5 {
+      app.scroll(10, 2, true);
+    }
+    assert_eq!(app.window(PanelKind::EcpuFreq), 7 * 24 * 3600);
+    assert_eq!(app.ecpu_freq.items.tier_for(7 * 24 * 3600 * 1000), 2);
+    let lines = render(&mut app, 120, 30);
+    assert!(lines.iter().any(|x| x.contains("Last 7d")));
+
+    app.click(0, 0);
+    assert!(app.zoom.is_none());
+  }
+
+  #[test]
+  fn test_render_power_stack() {
+    let mut app = test_app();
+    app.cfg.layout = LayoutNode::Panel { size: 1, panel: PanelKind::PowerStack, view: None };
+
+    let lines = render(&mut app, 100, 12);
+    assert!(lines[0].contains("Power 5.00W"));
+    assert!(lines[0].contains("█ CPU 1.50W") && lines[0].contains("░ Other 0.50W"));
+    assert!(lines[0].contains("· Rest 2.50W"));
+
+    // cpu 1.5W, gpu 0.5W, other 0.5W and rest 2.5W stacked on 10 rows from the bottom
+    let column = lines[1..11].iter().map(|x| x.chars().nth(98).unwrap()).collect::<String>();
+    assert_eq!(column, "·····░▓███");
+  }
+
+  #[test]
+  fn test_power_stack_aligned() {
+    let mut app = App::with_config(Config::default(), SocInfo::default());
+    app.cfg.layout = LayoutNode::Panel { size: 1, panel: PanelKind::PowerStack, view: None };
+
+    // GPU is missing in the newest sample, its older value stays in the older column
+    let ts = now_ms() - 5000;
+    for (ts, cpu, gpu) in [(ts, 2.0, Some(1.0)), (ts + 1000, 1.0, None)] {
+      app.cpu_power.push(ts, Some(cpu));
+      app.gpu_power.push(ts, gpu);
+      app.sys_power.push(ts, Some(3.0));
+    }
+
+    let lines = render(&mut app, 100, 12);
+    let column =
+      |x: usize| lines[1..11].iter().map(|l| l.chars().nth(x).unwrap()).collect::<String>();
+    assert_eq!(column(98), "·······███");
+    assert_eq!(column(97), "▓▓▓███████");
+  }
+
+  #[test]
+  fn test_render_cores() {
+    let mut app = test_app();
+    app.cfg.layout = LayoutNode::Panel { size: 1, panel: PanelKind::CpuCores, view: None };
+
+    let lines = render(&mut app, 60, 12);
+    assert!(lines[0].contains("Cores"));
+    assert!(lines[1].contains("E0  25%") && lines[1].contains("P0  50%"));
+
+    app.update_metrics(Metrics::default());
+    let lines = render(&mut app, 60, 12);
+    assert!(lines[0].contains("Cores N/A"));
+  }
+
+  #[test]
+  fn test_render_temp_groups() {
+    let mut app = test_app();
+    app.cfg.layout = LayoutNode::Panel { size: 1, panel: PanelKind::TempGroups, view: None };
+
+    let lines = render(&mut app, 60, 12);
+    assert!(lines[0].contains("Sensors N/A"));
+
+    let ssd = TempGroup { avg: 38.0, max: 41.0, hotspot: "TH0x".to_string(), ..Default::default() };
+    let groups =
+      BTreeMap::from([("ssd".to_string(), ssd), (CPU.to_string(), TempGroup::default())]);
+    app.update_metrics(Metrics {
+      temp: Some(TempMetrics { cpu_temp_avg: Some(45.0), gpu_temp_avg: Some(40.0), groups }),
+      ..Default::default()
+    });
+    let lines = render(&mut app, 60, 12);
+    assert!(lines[0].contains("Sensors"));
+    assert!(lines[1].contains("SSD 38.0°C (max TH0x 41.0°C)"), "{}", lines.join("\n"));
+    assert!(!lines[2].contains("CPU"));
+  }
+
+  #[test]
+  fn test_render_ascii_chart() {
+    let mut app = test_app();
+    app.cfg.ascii = true;
+    app.cfg.layout = LayoutNode::Panel { size: 1, panel: PanelKind::GpuFreq, view: None };
+    app.cfg.view_type = ViewType::Chart;
+
+    // last line is controls hint
+    let lines = render(&mut app, 100, 12);
+    assert!(lines[..11].iter().all(|x| x.is_ascii()), "{}", lines.join("\n"));
+    assert!(lines.iter().any(|x| x.contains('*')));
+  }
+
+  #[test]
+  fn test_render_power_group() {
+    let mut app = test_app();
+    let power = |view| LayoutNode::Panel { size: 1, panel: PanelKind::CpuPower, view };
+    let children = vec![power(Some(ViewType::Chart)), power(None)];
+
+    // one plot only when every panel of the group is in chart view
+    app.cfg.layout = LayoutNode::Row { size: 1, title: Some(GroupTitle::Power), children };
+    let lines = render(&mut app, 100, 12);
+    assert_eq!(lines.iter().map(|x| x.matches("CPU 1.50W (").count()).sum::<usize>(), 2);
+
+    app.cfg.view_type = ViewType::Chart;
+    let lines = render(&mut app, 100, 24);
+    assert!(!lines.iter().any(|x| x.contains("CPU 1.50W (")));
+    assert!(lines.iter().any(|x| x.contains("CPU 1.50W 45.0°C")));
+  }
+
+  #[test]
+  fn test_render_controls_hint() {
+    let mut app = test_app();
+    app.cfg.layout = LayoutNode::Panel { size: 1, panel: PanelKind::GpuFreq, view: None };
+
+    let lines = render(&mut app, 100, 12);
+    assert!(lines[11].contains("Press 'q' to quit"), "{}", lines[11]);
+  }
+
+  #[test]
+  fn test_render_notice() {
+    let mut app = test_app();
+    app.cfg.layout = LayoutNode::Panel { size: 1, panel: PanelKind::GpuFreq, view: None };
+    app.notice = Some(("Saved macmon-1.txt".to_string(), u64::MAX));
+
+    // layout without titles
+    let lines = render(&mut app, 100, 12);
+    assert!(lines[11].contains(" Saved macmon-1.txt ") && lines[11].contains("Press 'q'"));
+
+    let lines = render(&mut app, 120, 1);
+    assert!(lines[0].contains("· Saved macmon-1.txt"));
+    let lines = render(&mut app, 100, 3);
+    assert!(lines[0].contains("(Total 5.00W) · Saved macmon-1.txt"));
+    let lines = render(&mut app, 40, 40);
+    assert!(lines[39].contains("Saved macmon-1.txt"));
+
+    app.notice = Some(("Saved macmon-1.txt".to_string(), 0));
+    let lines = render(&mut app, 100, 12);
+    assert!(!lines[11].contains("Saved"));
+  }
+
+  #[test]
+  fn test_snapshot_matches_screen() {
+    let mut app = test_app();
+    let mut term = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    let frame = term.draw(|f| app.render(f)).unwrap();
+
+    let text = snapshot::export(frame.buffer, Format::Text);
+    let lines = render(&mut app, 100, 30);
+    let expected = lines.iter().map(|x| format!("{}\n", x.trim_end())).collect::<String>();
+    assert_eq!(text, expected);
+  }
+
+  #[test]
+  fn test_render_alerts() {
+    let mut app = test_app();
+    app.update_metrics(Metrics {
+      temp: Some(TempMetrics {
+        cpu_temp_avg: Some(102.0),
+        gpu_temp_avg: Some(40.0),
+        ..Default::default()
+      }),
+      ..Default::default()
+    });
+
+    let lines = render(&mut app, 100, 1);
+    assert!(lines[0].contains("· ⚠ CPU temp"), "{}", lines[0]);
+
+    let lines = render(&mut app, 100, 30);
+    assert!(lines[0].contains(" ⚠ CPU temp "), "{}", lines[0]);
+
+    app.cfg.ascii = true;
+    let lines = render(&mut app, 100, 1);
+    assert!(lines[0].contains("· ! CPU temp"), "{}", lines[0]);
+    let lines = render(&mut app, 100, 30);
+    assert!(lines[0].contains(" ! CPU temp "), "{}", lines[0]);
+  }
+
+  #[test]
+  fn test_render_mini() {
+    let mut app = test_app();
+    let lines = render(&mut app, 80, 3);
+
+    assert!(lines[0].starts_with("Apple M2 · Power 2.00W (Total 5.00W)"));
+    assert!(lines[1].contains("E  25%") && lines[1].contains("P  50%"));
+    assert!(lines[1].contains("GPU  10%"));
+    assert!(lines[2].contains("RAM 8.0GiB S 1.0GiB") && lines[2].contains("CPU 45°C GPU 40°C"));
+  }
+
+  #[test]
+  fn test_render_stack() {
+    let mut app = test_app();
+    let lines = render(&mut app, 40, 40);
+    let find = |s: &str| lines.iter().position(|x| x.contains(s)).unwrap();
+
+    assert!(lines[0].contains("Apple M2"));
+    assert!(find("E-CPU") < find("P-CPU"));
+    assert!(find("P-CPU") < find("RAM"));
+    assert!(find("RAM") < find("GPU  10%"));
+    assert!(find("GPU  10%") < find("ANE 0.00W"));
+  }
+
+  #[test]
+  fn test_render_full() {
+    let mut app = test_app();
+    let lines = render(&mut app, 120, 30);
+
+    assert!(lines[0].contains("Apple M2 (4E+4P+10GPU 16GiB)"));
+    assert!(
+      lines[1].contains("E-CPU  25% @ 1000 MHz") && lines[1].contains("P-CPU  50% @ 2000 MHz")
+    );
+    assert!(lines.iter().any(|x| x.contains("Power: 2.00W") && x.contains("Total 5.00W")));
+    assert!(lines.iter().any(|x| x.contains("CPU 1.50W") && x.contains("45.0°C")));
+  }
+}
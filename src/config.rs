@@ -1,74 +1,628 @@
 use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
-const COLORS_OPTIONS: [Color; 7] =
-  [Color::Green, Color::Yellow, Color::Red, Color::Blue, Color::Magenta, Color::Cyan, Color::Reset];
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+use crate::alerts::{Metric, Threshold};
+use crate::history::{default_tiers, TierSpec};
+use crate::keys::{Action, KeyMap};
+use crate::sensors::SensorsConfig;
+use crate::snapshot::Format;
+use crate::theme::{Theme, DEFAULT_THEME};
+use crate::units::Units;
+
+// msec, steps used by `+` / `-` keys
+pub const MIN_INTERVAL: u64 = 100;
+pub const MAX_INTERVAL: u64 = 10000;
+const INTERVAL_OPTIONS: [u64; 8] = [MIN_INTERVAL, 250, 500, 1000, 2000, 3000, 5000, MAX_INTERVAL];
+pub const DEFAULT_INTERVAL: u64 = 1000;
+
+// Written to the file, older files are migrated on load
+pub const CONFIG_VERSION: u32 = 2;
+
+// seconds, chart view time windows: 1m / 5m / 15m / 1h
+const WINDOW_OPTIONS: [u64; 4] = [60, 300, 900, 3600];
+
+// seconds, mouse scroll also reaches 6h / 1d / 7d, drawn from the coarse history tiers
+const SCROLL_WINDOW_OPTIONS: [u64; 7] = [60, 300, 900, 3600, 6 * 3600, 24 * 3600, 7 * 24 * 3600];
+
+// Next larger (`up`) or smaller value of sorted options, stays at the ends
+fn step(options: &[u64], value: u64, up: bool) -> u64 {
+  let (first, last) = (options[0], options[options.len() - 1]);
+  match up {
+    true => *options.iter().find(|&&x| x > value).unwrap_or(&last),
+    false => *options.iter().rev().find(|&&x| x < value).unwrap_or(&first),
+  }
+}
+
+fn default_interval() -> u64 {
+  DEFAULT_INTERVAL
+}
+
+fn default_window() -> u64 {
+  WINDOW_OPTIONS[0]
+}
+
+fn default_theme() -> String {
+  DEFAULT_THEME.to_string()
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, clap::ValueEnum)]
 pub enum ViewType {
+  #[default]
   Sparkline,
   Gauge,
+  Chart,
+}
+
+// MARK: Layout
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum PanelKind {
+  EcpuFreq,
+  PcpuFreq,
+  GpuFreq,
+  Memory,
+  CpuPower,
+  GpuPower,
+  AnePower,
+  AllPower,
+  SysPower,
+  CpuTemp,
+  GpuTemp,
+  PowerStack,
+  CpuCores,
+  TempGroups,
+}
+
+// Bordered block around a group: `soc` – chip info header, `power` – power totals and controls
+// (`power` group draws its power panels on one plot when all of them are in chart view)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupTitle {
+  Soc,
+  Power,
+}
+
+// Dashboard tree: `row` places children left to right, `column` – top to bottom,
+// `size` is relative to siblings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum LayoutNode {
+  Row {
+    #[serde(default = "default_size")]
+    size: u16,
+    #[serde(default)]
+    title: Option<GroupTitle>,
+    children: Vec<LayoutNode>,
+  },
+  Column {
+    #[serde(default = "default_size")]
+    size: u16,
+    #[serde(default)]
+    title: Option<GroupTitle>,
+    children: Vec<LayoutNode>,
+  },
+  Panel {
+    #[serde(default = "default_size")]
+    size: u16,
+    panel: PanelKind,
+    #[serde(default)]
+    view: Option<ViewType>, // overrides global view type
+  },
+}
+
+impl LayoutNode {
+  pub fn size(&self) -> u16 {
+    match self {
+      LayoutNode::Row { size, .. } => *size,
+      LayoutNode::Column { size, .. } => *size,
+      LayoutNode::Panel { size, .. } => *size,
+    }
+  }
+
+  // Panels of the tree, depth first
+  pub fn panels(&self) -> Vec<(PanelKind, Option<ViewType>)> {
+    match self {
+      LayoutNode::Row { children, .. } | LayoutNode::Column { children, .. } => {
+        children.iter().flat_map(|x| x.panels()).collect()
+      }
+      LayoutNode::Panel { panel, view, .. } => vec![(*panel, *view)],
+    }
+  }
+}
+
+fn default_size() -> u16 {
+  1
+}
+
+fn panel(panel: PanelKind) -> LayoutNode {
+  LayoutNode::Panel { size: 1, panel, view: None }
+}
+
+pub fn default_layout() -> LayoutNode {
+  use PanelKind::*;
+
+  let row = |children| LayoutNode::Row { size: 1, title: None, children };
+  let soc = LayoutNode::Column {
+    size: 2,
+    title: Some(GroupTitle::Soc),
+    children: vec![
+      row(vec![panel(EcpuFreq), panel(PcpuFreq)]),
+      row(vec![panel(Memory), panel(GpuFreq)]),
+    ],
+  };
+
+  let power = LayoutNode::Row {
+    size: 1,
+    title: Some(GroupTitle::Power),
+    children: vec![panel(CpuPower), panel(GpuPower), panel(AnePower)],
+  };
+
+  LayoutNode::Column { size: 1, title: None, children: vec![soc, power] }
+}
+
+// MARK: Migrations
+
+// 1 → 2: single `color` option replaced with named themes
+fn migrate_color(map: &mut Map<String, Value>) {
+  if let Some(color) = map.remove("color") {
+    let color = serde_json::from_value::<Color>(color).ok();
+    let name = color.and_then(Theme::name_for_color).unwrap_or(DEFAULT_THEME);
+    map.entry("theme").or_insert(name.into());
+  }
+}
+
+type Migration = fn(&mut Map<String, Value>);
+
+// (version, migration to the next one), files without `version` are version 1
+const MIGRATIONS: [(u32, Migration); 1] = [(1, migrate_color)];
+
+// MARK: Config
+
+// `--config` path, then `$XDG_CONFIG_HOME/macmon.json`, then `~/.config/macmon.json`;
+// the legacy `~/.config` file is kept in use until a file exists in the XDG dir
+fn config_path(
+  explicit: Option<&str>,
+  xdg: Option<String>,
+  home: Option<String>,
+) -> Option<PathBuf> {
+  if let Some(path) = explicit {
+    return Some(PathBuf::from(path));
+  }
+
+  let legacy = home.map(|x| Path::new(&x).join(".config").join("macmon.json"));
+
+  // relative XDG paths are invalid by the spec and ignored
+  match xdg.filter(|x| Path::new(x).is_absolute()) {
+    Some(xdg) => {
+      let path = Path::new(&xdg).join("macmon.json");
+      match legacy {
+        Some(legacy) if !path.exists() && legacy.exists() => Some(legacy),
+        _ => Some(path),
+      }
+    }
+    None => legacy,
+  }
+}
+
+fn file_mtime(path: &Path) -> Option<SystemTime> {
+  std::fs::metadata(path).and_then(|x| x.modified()).ok()
+}
+
+// Command line values, applied on top of the file and the profile
+#[derive(Debug, Default)]
+pub struct Overrides {
+  pub profile: Option<String>,
+  pub interval: Option<u64>,
+  pub theme: Option<String>,
+  pub view_type: Option<ViewType>,
+  pub ascii: bool,
+}
+
+// Named set of values replacing top-level ones while active
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Profile {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub view_type: Option<ViewType>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub theme: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub layout: Option<LayoutNode>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub interval: Option<u64>, // msec
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub chart_window: Option<u64>, // seconds
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub thresholds: Option<BTreeMap<Metric, Threshold>>,
+}
+
+impl Profile {
+  const KEYS: [&'static str; 6] =
+    ["view_type", "theme", "layout", "interval", "chart_window", "thresholds"];
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
+  #[serde(default)]
+  pub version: u32,
+  #[serde(default)]
+  pub profile: Option<String>, // active profile
+  #[serde(default)]
+  pub profiles: BTreeMap<String, Profile>,
+  #[serde(default)]
   pub view_type: ViewType,
-  pub color: Color,
+  #[serde(default = "default_theme")]
+  pub theme: String,
+  #[serde(default)]
+  pub themes: BTreeMap<String, Theme>, // user-defined themes, can override built-in ones
+  #[serde(default)]
+  pub ascii: bool, // ASCII-only borders and bars for limited terminals
+  #[serde(default = "default_interval")]
+  pub interval: u64, // msec
+  #[serde(default)]
+  pub save_interval: bool, // interval changed with `+` / `-` is written to the file
+  #[serde(default = "default_tiers")]
+  pub history: Vec<TierSpec>, // retention tiers, from most to least detailed
+  #[serde(default = "default_window")]
+  pub chart_window: u64, // seconds
+  #[serde(default = "default_layout")]
+  pub layout: LayoutNode,
+  #[serde(default)]
+  pub thresholds: BTreeMap<Metric, Threshold>, // overrides of per-chip defaults
+  #[serde(default)]
+  pub keys: BTreeMap<Action, Vec<String>>, // replaces default keys of listed actions
+  #[serde(default)]
+  pub snapshot_format: Format, // used by snapshot key
+  #[serde(default)]
+  pub units: Units, // display units of temperature, memory, frequency and power
+  #[serde(default)]
+  pub sensors: SensorsConfig, // temperature sensor groups and calibration offsets
+
+  #[serde(skip)]
+  path: Option<PathBuf>,
+  #[serde(skip)]
+  mtime: Option<SystemTime>, // of the file when it was last read or written
+  #[serde(skip)]
+  readonly: bool, // file failed to parse or is newer, never overwritten
+  #[serde(skip)]
+  base: Map<String, Value>, // values of the file
+  #[serde(skip)]
+  overrides: Map<String, Value>, // values from command line
+  #[serde(skip)]
+  layered: Map<String, Value>, // file + profile + command line, to find changes made in the app
+  #[serde(skip)]
+  pub warnings: Vec<String>,
 }
 
 impl Config {
-  fn get_config_path() -> Option<String> {
-    let home = match std::env::var("HOME") {
-      Ok(home) => home,
-      Err(_) => return None,
+  pub fn load(path: Option<&str>) -> Self {
+    let xdg = std::env::var("XDG_CONFIG_HOME").ok().filter(|x| !x.is_empty());
+    let Some(path) = config_path(path, xdg, std::env::var("HOME").ok()) else {
+      return Self::default();
+    };
+
+    let mut config = Self::read(&path);
+    config.mtime = file_mtime(&path);
+    config.path = Some(path);
+    config.resolve();
+    config
+  }
+
+  // Re-reads the file if it was changed since last read or write, None when not changed.
+  // On error current values are kept and the file is not overwritten until fixed.
+  pub fn reload(&mut self) -> Option<Result<(), String>> {
+    let path = self.path.clone()?;
+    let mtime = file_mtime(&path);
+    if mtime.is_none() || mtime == self.mtime {
+      return None;
+    }
+    self.mtime = mtime;
+
+    let parsed = std::fs::read_to_string(&path).map_err(|e| e.to_string());
+    // key bindings are checked too, so nothing is applied when any part fails
+    let parsed = parsed.and_then(|x| Self::parse(&x));
+    let mut config = match parsed.and_then(|x| KeyMap::new(&x.keys).map(|_| x)) {
+      Ok(config) => config,
+      Err(e) => {
+        self.readonly = true;
+        return Some(Err(format!("config: {}", e)));
+      }
+    };
+
+    // CLI values are kept for keys not changed in the file
+    let mut overrides = std::mem::take(&mut self.overrides);
+    overrides.retain(|key, _| self.base.get(key) == config.base.get(key));
+
+    config.path = Some(path);
+    config.mtime = mtime;
+    config.overrides = overrides;
+    config.resolve();
+    *self = config;
+    Some(Ok(()))
+  }
+
+  fn read(path: &Path) -> Self {
+    let text = match std::fs::read_to_string(path) {
+      Ok(text) => text,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+      Err(e) => return Self::failed(path, &e.to_string()),
+    };
+
+    match Self::parse(&text) {
+      Ok(config) => config,
+      Err(e) => Self::failed(path, &e),
+    }
+  }
+
+  // Defaults for this session, broken file is kept to be fixed by hand
+  fn failed(path: &Path, error: &str) -> Self {
+    let msg =
+      format!("config: {}: {}, using defaults, file will not be changed", path.display(), error);
+    Self { readonly: true, warnings: vec![msg], ..Self::default() }
+  }
+
+  fn to_map(&self) -> Map<String, Value> {
+    match serde_json::to_value(self) {
+      Ok(Value::Object(map)) => map,
+      _ => Map::new(),
+    }
+  }
+
+  fn parse(text: &str) -> Result<Self, String> {
+    let mut value = serde_json::from_str::<Value>(text).map_err(|e| e.to_string())?;
+    let Some(map) = value.as_object_mut() else {
+      return Err("expected JSON object".to_string());
     };
 
-    let filepath = format!("{}/.config/macmon.json", home);
-    let _ = std::fs::create_dir_all(std::path::Path::new(&filepath).parent().unwrap());
-    Some(filepath)
+    let mut warnings = Vec::new();
+    let version = map.get("version").and_then(|x| x.as_u64()).unwrap_or(1) as u32;
+    let readonly = version > CONFIG_VERSION;
+    if readonly {
+      let msg =
+        format!("config: version {} is newer than supported, file will not be changed", version);
+      warnings.push(msg);
+    }
+
+    for (from, migrate) in MIGRATIONS {
+      if version <= from {
+        migrate(map);
+      }
+    }
+    map.insert("version".to_string(), version.max(CONFIG_VERSION).into());
+
+    let known = Self::default().to_map();
+    for key in map.keys().filter(|x| !known.contains_key(*x)) {
+      warnings.push(format!("config: unknown key '{}' ignored", key));
+    }
+
+    let profiles = map.get("profiles").and_then(|x| x.as_object()).into_iter().flatten();
+    for (name, profile) in profiles {
+      let keys = profile.as_object().into_iter().flat_map(|x| x.keys());
+      for key in keys.filter(|x| !Profile::KEYS.contains(&x.as_str())) {
+        warnings.push(format!("config: unknown key 'profiles.{}.{}' ignored", name, key));
+      }
+    }
+
+    let mut config = serde_json::from_value::<Self>(value).map_err(|e| e.to_string())?;
+    config.readonly = readonly;
+    config.warnings = warnings;
+    config.base = config.to_map();
+    Ok(config)
+  }
+
+  // Effective values: file, then active profile, then command line
+  fn resolve(&mut self) {
+    if self.base.is_empty() {
+      self.base = self.to_map();
+    }
+
+    let mut map = self.base.clone();
+    if let Some(name) = self.overrides.get("profile") {
+      map.insert("profile".to_string(), name.clone());
+    }
+
+    if let Some(name) = map.get("profile").and_then(|x| x.as_str()).map(|x| x.to_string()) {
+      match map.get("profiles").and_then(|x| x.get(&name)).and_then(|x| x.as_object()).cloned() {
+        Some(profile) => map.extend(profile),
+        None => {
+          self.warnings.push(format!("config: unknown profile '{}'", name));
+          map.insert("profile".to_string(), Value::Null);
+        }
+      }
+    }
+
+    map.extend(self.overrides.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+    if let Ok(mut config) = serde_json::from_value::<Self>(Value::Object(map)) {
+      config.path = self.path.take();
+      config.mtime = self.mtime;
+      config.readonly = self.readonly;
+      config.base = std::mem::take(&mut self.base);
+      config.overrides = std::mem::take(&mut self.overrides);
+      config.warnings = std::mem::take(&mut self.warnings);
+      *self = config;
+    }
+
+    self.clamp_values();
+    self.layered = self.to_map();
   }
 
-  pub fn load() -> Self {
-    if let Some(path) = Self::get_config_path() {
-      let file = match std::fs::File::open(path) {
-        Ok(file) => file,
-        Err(_) => return Self::default(),
-      };
+  // Out of range values are used clamped to the key options, the file keeps them as written
+  fn clamp_values(&mut self) {
+    let ranges = [
+      ("interval", &mut self.interval, MIN_INTERVAL, MAX_INTERVAL),
+      (
+        "chart_window",
+        &mut self.chart_window,
+        WINDOW_OPTIONS[0],
+        WINDOW_OPTIONS[WINDOW_OPTIONS.len() - 1],
+      ),
+    ];
 
-      let reader = std::io::BufReader::new(file);
-      return match serde_json::from_reader(reader) {
-        Ok(config) => config,
-        Err(_) => Self::default(),
-      };
+    for (key, value, min, max) in ranges {
+      if *value < min || *value > max {
+        let clamped = (*value).clamp(min, max);
+        self
+          .warnings
+          .push(format!("config: {} {} is out of range, {} is used", key, value, clamped));
+        *value = clamped;
+      }
     }
+  }
+
+  pub fn apply_overrides(&mut self, args: Overrides) {
+    let mut values = Map::new();
+    let mut set = |key: &str, value: Value| values.insert(key.to_string(), value);
 
-    Self::default()
+    if let Some(profile) = args.profile {
+      set("profile", profile.into());
+    }
+    if let Some(interval) = args.interval {
+      set("interval", interval.clamp(MIN_INTERVAL, MAX_INTERVAL).into());
+    }
+    if let Some(theme) = args.theme {
+      if !self.theme_names().contains(&theme) {
+        self.warnings.push(format!("config: unknown theme '{}'", theme));
+      }
+      set("theme", theme.into());
+    }
+    if let Some(view_type) = args.view_type {
+      set("view_type", serde_json::to_value(view_type).unwrap_or_default());
+    }
+    if args.ascii {
+      set("ascii", true.into());
+    }
+
+    self.overrides = values;
+    self.resolve();
   }
 
-  pub fn save(&self) {
-    if let Some(path) = Self::get_config_path() {
-      let file = match std::fs::File::create(path) {
-        Ok(file) => file,
-        Err(_) => return,
-      };
+  // Values changed in the app are saved to the active profile when it sets them, otherwise
+  // to the top level; profile and command line values are kept out of the top level
+  pub fn save(&mut self) {
+    let Some(path) = self.path.clone().filter(|_| !self.readonly) else { return };
+
+    let current = self.to_map();
+    let mut map = self.base.clone();
+    for (key, value) in &current {
+      if key == "profiles" || self.layered.get(key) == Some(value) {
+        continue;
+      }
+
+      let profile = self
+        .profile
+        .as_ref()
+        .and_then(|name| map.get_mut("profiles")?.get_mut(name)?.as_object_mut()?.get_mut(key));
+      match profile {
+        Some(x) => *x = value.clone(),
+        None => {
+          map.insert(key.clone(), value.clone());
+        }
+      }
+    }
+
+    self.base = map;
+    self.layered = current;
 
-      let writer = std::io::BufWriter::new(file);
-      let _ = serde_json::to_writer_pretty(writer, self);
+    if let Some(dir) = path.parent() {
+      let _ = std::fs::create_dir_all(dir);
     }
+
+    let file = match std::fs::File::create(&path) {
+      Ok(file) => file,
+      Err(_) => return,
+    };
+
+    let writer = std::io::BufWriter::new(file);
+    let _ = serde_json::to_writer_pretty(writer, &self.base);
+
+    // own writes are not reloaded
+    self.mtime = file_mtime(&path);
+  }
+
+  // Cycles through profiles, "no profile" included. Command line values of profile keys are
+  // dropped, others (e.g. `--ascii`) are kept
+  pub fn next_profile(&mut self) {
+    let mut names = vec![None];
+    names.extend(self.profiles.keys().cloned().map(Some));
+
+    let idx = names.iter().position(|x| *x == self.profile).map_or(0, |x| x + 1);
+    let name = names[idx % names.len()].clone();
+    if name == self.profile {
+      return;
+    }
+
+    self.overrides.retain(|key, _| key != "profile" && !Profile::KEYS.contains(&key.as_str()));
+    self.base.insert("profile".to_string(), serde_json::to_value(&name).unwrap_or_default());
+    self.resolve();
+    self.save();
   }
 
-  pub fn next_color(&mut self) {
-    self.color = match COLORS_OPTIONS.iter().position(|&c| c == self.color) {
-      Some(idx) => COLORS_OPTIONS[(idx + 1) % COLORS_OPTIONS.len()],
-      None => COLORS_OPTIONS[0],
+  fn theme_names(&self) -> Vec<String> {
+    let mut names = Theme::builtin_names();
+    let custom = self.themes.keys().filter(|x| !names.contains(x)).cloned().collect::<Vec<_>>();
+    names.extend(custom);
+    names
+  }
+
+  pub fn next_theme(&mut self) {
+    let names = self.theme_names();
+    self.theme = match names.iter().position(|x| *x == self.theme) {
+      Some(idx) => names[(idx + 1) % names.len()].clone(),
+      None => names[0].clone(),
     };
     self.save();
   }
 
+  // Current theme, NO_COLOR env var takes precedence
+  pub fn get_theme(&self) -> Theme {
+    if Theme::no_color() {
+      return Theme::colorless();
+    }
+
+    match self.themes.get(&self.theme) {
+      Some(theme) => theme.clone(),
+      None => Theme::builtin(&self.theme).or(Theme::builtin(DEFAULT_THEME)).unwrap(),
+    }
+  }
+
+  pub fn next_interval(interval: u64, slower: bool) -> u64 {
+    step(&INTERVAL_OPTIONS, interval, slower)
+  }
+
+  // Without `save_interval` the value is for the session only, other saves don't write it
+  pub fn set_interval(&mut self, interval: u64) {
+    self.interval = interval;
+    match self.save_interval {
+      true => self.save(),
+      false => {
+        self.layered.insert("interval".to_string(), interval.into());
+      }
+    }
+  }
+
   pub fn next_view_type(&mut self) {
     self.view_type = match self.view_type {
       ViewType::Sparkline => ViewType::Gauge,
-      ViewType::Gauge => ViewType::Sparkline,
+      ViewType::Gauge => ViewType::Chart,
+      ViewType::Chart => ViewType::Sparkline,
+    };
+    self.save();
+  }
+
+  pub fn next_window(window: u64, longer: bool) -> u64 {
+    step(&SCROLL_WINDOW_OPTIONS, window, longer)
+  }
+
+  pub fn next_chart_window(&mut self) {
+    self.chart_window = match WINDOW_OPTIONS.iter().position(|&x| x == self.chart_window) {
+      Some(idx) => WINDOW_OPTIONS[(idx + 1) % WINDOW_OPTIONS.len()],
+      None => WINDOW_OPTIONS[0],
     };
     self.save();
   }
@@ -76,6 +630,298 @@ impl Config {
 
 impl Default for Config {
   fn default() -> Self {
-    Self { color: COLORS_OPTIONS[0], view_type: ViewType::Sparkline }
+    Self {
+      version: CONFIG_VERSION,
+      profile: None,
+      profiles: BTreeMap::new(),
+      theme: default_theme(),
+      themes: BTreeMap::new(),
+      ascii: false,
+      view_type: ViewType::default(),
+      interval: DEFAULT_INTERVAL,
+      save_interval: false,
+      history: default_tiers(),
+      chart_window: default_window(),
+      layout: default_layout(),
+      thresholds: BTreeMap::new(),
+      keys: BTreeMap::new(),
+      snapshot_format: Format::default(),
+      units: Units::default(),
+      sensors: SensorsConfig::default(),
+      path: None,
+      mtime: None,
+      readonly: false,
+      base: Map::new(),
+      overrides: Map::new(),
+      layered: Map::new(),
+      warnings: Vec::new(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn temp_file(name: &str, text: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("macmon-{}-{}.json", name, std::process::id()));
+    std::fs::write(&path, text).unwrap();
+    path
+  }
+
+  #[test]
+  fn test_config_path() {
+    let home = Some("/Users/me".to_string());
+    let path = |explicit, xdg: Option<&str>| {
+      config_path(explicit, xdg.map(|x| x.to_string()), home.clone()).unwrap()
+    };
+
+    assert_eq!(path(None, None), PathBuf::from("/Users/me/.config/macmon.json"));
+    assert_eq!(path(None, Some("/xdg")), PathBuf::from("/xdg/macmon.json"));
+    assert_eq!(path(None, Some("xdg")), PathBuf::from("/Users/me/.config/macmon.json"));
+    assert_eq!(path(Some("my.json"), Some("/xdg")), PathBuf::from("my.json"));
+    assert_eq!(config_path(None, None, None), None);
+  }
+
+  #[test]
+  fn test_config_path_legacy() {
+    let root = std::env::temp_dir().join(format!("macmon-home-{}", std::process::id()));
+    let (home, xdg) = (root.join("home"), root.join("xdg"));
+    let legacy = home.join(".config").join("macmon.json");
+    std::fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+    std::fs::create_dir_all(&xdg).unwrap();
+
+    let dir = |x: &Path| Some(x.to_string_lossy().to_string());
+    let path = || config_path(None, dir(&xdg), dir(&home)).unwrap();
+
+    // no files yet, new settings go to the XDG dir
+    assert_eq!(path(), xdg.join("macmon.json"));
+
+    // existing legacy file is still read and saved to
+    std::fs::write(&legacy, "{}").unwrap();
+    assert_eq!(path(), legacy);
+
+    // XDG file wins once it exists
+    std::fs::write(xdg.join("macmon.json"), "{}").unwrap();
+    assert_eq!(path(), xdg.join("macmon.json"));
+
+    let _ = std::fs::remove_dir_all(root);
+  }
+
+  #[test]
+  fn test_migrate_legacy() {
+    let config = Config::parse(r#"{ "view_type": "Gauge", "color": "Red" }"#).unwrap();
+    assert_eq!(config.version, CONFIG_VERSION);
+    assert_eq!(config.view_type, ViewType::Gauge);
+    assert_eq!(config.theme, "red");
+    assert!(config.warnings.is_empty());
+  }
+
+  #[test]
+  fn test_unknown_keys() {
+    let config = Config::parse(r#"{ "version": 2, "intervall": 500, "ascii": true }"#).unwrap();
+    assert_eq!(config.warnings, vec!["config: unknown key 'intervall' ignored"]);
+    assert!(config.ascii && !config.readonly);
+
+    let config = Config::parse(r#"{ "version": 99 }"#).unwrap();
+    assert!(config.readonly);
+
+    assert!(Config::parse(r#"{ "interval": "fast" }"#).is_err());
+    assert!(Config::parse("[]").is_err());
+  }
+
+  #[test]
+  fn test_broken_file_kept() {
+    let text = r#"{ "interval": 500, }"#;
+    let path = temp_file("broken", text);
+
+    let mut config = Config::load(path.to_str());
+    assert_eq!(config.interval, DEFAULT_INTERVAL);
+    assert_eq!(config.warnings.len(), 1);
+
+    config.next_view_type();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[test]
+  fn test_overrides_not_saved() {
+    let path = temp_file("overrides", r#"{ "interval": 2000, "theme": "red" }"#);
+
+    let mut config = Config::load(path.to_str());
+    let args =
+      Overrides { interval: Some(500), theme: Some("blue".to_string()), ..Default::default() };
+    config.apply_overrides(args);
+    assert_eq!((config.interval, config.theme.as_str()), (500, "blue"));
+
+    // saved by another setting, CLI values stay out of the file
+    config.next_view_type();
+    let saved = Config::load(path.to_str());
+    assert_eq!((saved.interval, saved.theme.as_str()), (2000, "red"));
+    assert_eq!(saved.view_type, ViewType::Gauge);
+
+    // value changed in the app is saved
+    config.next_theme();
+    assert_eq!(Config::load(path.to_str()).theme, config.theme);
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[test]
+  fn test_profiles() {
+    let text = r#"{
+      "profile": "llm",
+      "theme": "blue",
+      "profiles": {
+        "llm": { "view_type": "Chart", "chart_window": 3600, "interval": 2000 },
+        "build": { "theme": "red", "thresholds": { "cpu_temp": { "warning": 80 } }, "colour": 1 }
+      }
+    }"#;
+    let path = temp_file("profiles", text);
+
+    let mut config = Config::load(path.to_str());
+    assert_eq!(config.warnings, vec!["config: unknown key 'profiles.build.colour' ignored"]);
+    assert_eq!(
+      (config.view_type, config.chart_window, config.interval),
+      (ViewType::Chart, 3600, 2000)
+    );
+    assert_eq!(config.theme, "blue");
+
+    // alphabetical order after "no profile"
+    config.next_profile();
+    assert_eq!(config.profile, None);
+    assert_eq!((config.view_type, config.interval), (ViewType::Sparkline, DEFAULT_INTERVAL));
+    config.next_profile();
+    assert_eq!(config.profile.as_deref(), Some("build"));
+    assert_eq!(config.theme, "red");
+    assert!(config.thresholds.contains_key(&Metric::CpuTemp));
+
+    // changes go to the profile when it sets the value, otherwise to the top level
+    config.next_theme();
+    config.next_view_type();
+    let saved = Config::load(path.to_str());
+    assert_eq!(saved.profile.as_deref(), Some("build"));
+    assert_eq!(saved.profiles["build"].theme.as_ref(), Some(&config.theme));
+    assert_eq!(saved.base["theme"], "blue");
+    assert_eq!(saved.view_type, ViewType::Gauge);
+
+    // CLI values of other keys are kept
+    let mut config = Config::load(path.to_str());
+    let args = Overrides { ascii: true, interval: Some(500), ..Default::default() };
+    config.apply_overrides(args);
+    config.next_profile();
+    assert_eq!(config.profile.as_deref(), Some("llm"));
+    assert!(config.ascii);
+    assert_eq!(config.interval, 2000);
+    config.next_profile();
+    config.next_profile();
+    assert_eq!(config.profile.as_deref(), Some("build"));
+
+    // CLI profile is used for the session only
+    let mut config = Config::load(path.to_str());
+    config.apply_overrides(Overrides { profile: Some("llm".to_string()), ..Default::default() });
+    assert_eq!(config.view_type, ViewType::Chart);
+    config.next_chart_window();
+    let saved = Config::load(path.to_str());
+    assert_eq!(saved.profile.as_deref(), Some("build"));
+    assert_eq!(saved.profiles["llm"].chart_window, Some(60));
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[test]
+  fn test_step() {
+    assert_eq!(step(&INTERVAL_OPTIONS, 1000, true), 2000);
+    assert_eq!(step(&INTERVAL_OPTIONS, 1000, false), 500);
+    assert_eq!(step(&INTERVAL_OPTIONS, 700, true), 1000);
+    assert_eq!(step(&WINDOW_OPTIONS, 3600, true), 3600);
+    assert_eq!(step(&WINDOW_OPTIONS, 60, false), 60);
+    assert_eq!(Config::next_window(3600, true), 6 * 3600);
+    assert_eq!(Config::next_window(7 * 24 * 3600, true), 7 * 24 * 3600);
+  }
+
+  #[test]
+  fn test_save_interval() {
+    let path = temp_file("interval", r#"{ "interval": 2000 }"#);
+
+    // session only, other saves keep the file value
+    let mut config = Config::load(path.to_str());
+    config.set_interval(500);
+    config.next_view_type();
+    assert_eq!(Config::load(path.to_str()).interval, 2000);
+
+    std::fs::write(&path, r#"{ "interval": 2000, "save_interval": true }"#).unwrap();
+    let mut config = Config::load(path.to_str());
+    config.set_interval(500);
+    assert_eq!(Config::load(path.to_str()).interval, 500);
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[test]
+  fn test_clamp_values() {
+    let text =
+      r#"{ "interval": 50, "chart_window": 5, "profiles": { "slow": { "interval": 60000 } } }"#;
+    let path = temp_file("clamp", text);
+
+    let mut config = Config::load(path.to_str());
+    assert_eq!((config.interval, config.chart_window), (100, 60));
+    assert_eq!(config.warnings[0], "config: interval 50 is out of range, 100 is used");
+    assert_eq!(config.warnings[1], "config: chart_window 5 is out of range, 60 is used");
+
+    config.next_profile();
+    assert_eq!(config.interval, 10000);
+
+    // clamped values are not written back
+    config.next_view_type();
+    let saved = serde_json::from_str::<Value>(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!((saved["interval"].as_u64(), saved["chart_window"].as_u64()), (Some(50), Some(5)));
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[test]
+  fn test_single_profile_not_saved() {
+    let text = r#"{ "theme": "red" }"#;
+    let path = temp_file("no-profiles", text);
+
+    let mut config = Config::load(path.to_str());
+    config.next_profile();
+    assert_eq!(config.profile, None);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+    let _ = std::fs::remove_file(path);
+  }
+
+  fn touch(path: &Path, text: &str, secs: u64) {
+    std::fs::write(path, text).unwrap();
+    let file = std::fs::File::options().write(true).open(path).unwrap();
+    file.set_modified(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs)).unwrap();
+  }
+
+  #[test]
+  fn test_reload() {
+    let path = temp_file("reload", "{}");
+    let mut config = Config::load(path.to_str());
+    config.apply_overrides(Overrides { interval: Some(500), ..Default::default() });
+    assert_eq!(config.reload(), None);
+
+    // broken file keeps current values and is not overwritten
+    touch(&path, r#"{ "theme": "red", }"#, 1000);
+    assert!(matches!(config.reload(), Some(Err(_))));
+    assert_eq!(config.theme, DEFAULT_THEME);
+    config.next_view_type();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), r#"{ "theme": "red", }"#);
+
+    // fixed file is applied, CLI interval is kept until changed in the file
+    touch(&path, r#"{ "theme": "red" }"#, 2000);
+    assert_eq!(config.reload(), Some(Ok(())));
+    assert_eq!((config.theme.as_str(), config.interval), ("red", 500));
+    assert_eq!(config.reload(), None);
+
+    touch(&path, r#"{ "theme": "red", "interval": 3000 }"#, 3000);
+    assert_eq!(config.reload(), Some(Ok(())));
+    assert_eq!(config.interval, 3000);
+
+    // invalid key bindings keep all previous values
+    touch(&path, r#"{ "theme": "blue", "keys": { "help": ["hlp"] } }"#, 4000);
+    assert_eq!(config.reload(), Some(Err("config: keys.help: unknown key 'hlp'".to_string())));
+    assert_eq!(config.theme, "red");
+    let _ = std::fs::remove_file(path);
   }
 }
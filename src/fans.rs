@@ -0,0 +1,116 @@
+use crate::smc::decode;
+use crate::sources::SMC;
+
+type WithError<T> = Result<T, Box<dyn std::error::Error>>;
+
+// Source of raw SMC values: (type, data), a fake one is used in tests
+pub trait KeyStore {
+  fn read_key(&mut self, key: &str) -> WithError<(String, Vec<u8>)>;
+}
+
+impl KeyStore for SMC {
+  fn read_key(&mut self, key: &str) -> WithError<(String, Vec<u8>)> {
+    let val = self.read_val(key)?;
+    Ok((val.unit, val.data))
+  }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FanMetrics {
+  pub actual: f32,         // RPM
+  pub min: f32,            // RPM
+  pub max: f32,            // RPM
+  pub target: Option<f32>, // RPM, not set by every model
+}
+
+fn read_num(store: &mut impl KeyStore, key: &str) -> Option<f32> {
+  let (data_type, data) = store.read_key(key).ok()?;
+  decode(&data_type, &data)?.as_f64().map(|x| x as f32)
+}
+
+// Number of fans, zero on fanless machines (MacBook Air)
+pub fn fan_count(store: &mut impl KeyStore) -> usize {
+  read_num(store, "FNum").map_or(0, |x| x.max(0.0) as usize).min(10)
+}
+
+// Fans without readable actual speed are skipped
+pub fn read_fans(store: &mut impl KeyStore, count: usize) -> Vec<FanMetrics> {
+  let mut items = Vec::new();
+  for i in 0..count {
+    let mut read = |name: &str| read_num(store, &format!("F{}{}", i, name));
+    let Some(actual) = read("Ac") else { continue };
+
+    let (min, max) = (read("Mn").unwrap_or(0.0), read("Mx").unwrap_or(0.0));
+    items.push(FanMetrics { actual, min, max, target: read("Tg") });
+  }
+  items
+}
+
+// e.g. "Fan 1200rpm" or "Fans 1200/1250rpm", None without fans
+pub fn fmt_fans(fans: &[FanMetrics]) -> Option<String> {
+  let speeds = fans.iter().map(|x| format!("{:.0}", x.actual)).collect::<Vec<_>>();
+  match speeds.len() {
+    0 => None,
+    1 => Some(format!("Fan {}rpm", speeds[0])),
+    _ => Some(format!("Fans {}rpm", speeds.join("/"))),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashMap;
+
+  #[derive(Default)]
+  struct FakeStore(HashMap<String, (String, Vec<u8>)>);
+
+  impl FakeStore {
+    fn set(&mut self, key: &str, data_type: &str, data: &[u8]) {
+      self.0.insert(key.to_string(), (data_type.to_string(), data.to_vec()));
+    }
+
+    fn set_flt(&mut self, key: &str, value: f32) {
+      self.set(key, "flt ", &value.to_le_bytes());
+    }
+  }
+
+  impl KeyStore for FakeStore {
+    fn read_key(&mut self, key: &str) -> WithError<(String, Vec<u8>)> {
+      Ok(self.0.get(key).cloned().ok_or("SMC key not found")?)
+    }
+  }
+
+  #[test]
+  fn test_read_fans() {
+    let mut store = FakeStore::default();
+    store.set("FNum", "ui8 ", &[2]);
+    store.set_flt("F0Ac", 1203.5);
+    store.set_flt("F0Mn", 1200.0);
+    store.set_flt("F0Mx", 5779.0);
+    store.set_flt("F0Tg", 1200.0);
+    store.set("F1Ac", "fpe2", &[0x13, 0x88]); // 1250 rpm as fixed-point, older models
+
+    assert_eq!(fan_count(&mut store), 2);
+    let fans = read_fans(&mut store, 2);
+    assert_eq!(
+      fans[0],
+      FanMetrics { actual: 1203.5, min: 1200.0, max: 5779.0, target: Some(1200.0) }
+    );
+    assert_eq!(fans[1], FanMetrics { actual: 1250.0, ..Default::default() });
+    assert_eq!(fmt_fans(&fans).as_deref(), Some("Fans 1204/1250rpm"));
+  }
+
+  #[test]
+  fn test_fanless() {
+    let mut store = FakeStore::default();
+    assert_eq!(fan_count(&mut store), 0);
+    assert!(read_fans(&mut store, 0).is_empty());
+    assert_eq!(fmt_fans(&[]), None);
+
+    // reported fan without readable speed
+    store.set("FNum", "ui8 ", &[1]);
+    store.set("F0Ac", "flt ", &[0, 0]);
+    let count = fan_count(&mut store);
+    assert!(count == 1 && read_fans(&mut store, count).is_empty());
+  }
+}
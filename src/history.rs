@@ -0,0 +1,262 @@
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::metrics::zero_div;
+
+// MARK: Config
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct TierSpec {
+  pub step: u64, // seconds per point
+  pub keep: u64, // seconds of retention
+}
+
+// 1s for 10 minutes, 10s for 6 hours, 1m for 7 days
+pub const DEFAULT_TIERS: [TierSpec; 3] = [
+  TierSpec { step: 1, keep: 10 * 60 },
+  TierSpec { step: 10, keep: 6 * 60 * 60 },
+  TierSpec { step: 60, keep: 7 * 24 * 60 * 60 },
+];
+
+pub fn default_tiers() -> Vec<TierSpec> {
+  DEFAULT_TIERS.to_vec()
+}
+
+pub fn now_ms() -> u64 {
+  SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |x| x.as_millis() as u64)
+}
+
+// MARK: Tier
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Point {
+  pub ts: u64, // msec since unix epoch (last sample in bucket)
+  pub avg: f64,
+  pub max: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+  idx: u64,
+  ts: u64,
+  sum: f64,
+  max: f64,
+  count: u32,
+}
+
+impl Bucket {
+  fn point(&self) -> Point {
+    Point { ts: self.ts, avg: self.sum / self.count as f64, max: self.max }
+  }
+}
+
+// Ring of aggregated points with a pending bucket. Sum and max of stored points are kept
+// incrementally (max via monotonic queue), so push and stats are O(1) amortized.
+#[derive(Debug)]
+struct Tier {
+  step: u64, // msec
+  keep: u64, // msec
+  cap: usize,
+  items: VecDeque<Point>,
+  bucket: Option<Bucket>,
+  sum: f64,
+  maxq: VecDeque<(u64, f64)>, // (seq, max), decreasing by max
+  seq: u64,                   // seq of the next point
+}
+
+impl Tier {
+  fn new(spec: &TierSpec) -> Self {
+    let step = spec.step.max(1) * 1000;
+    let cap = (spec.keep / spec.step.max(1)).max(1) as usize;
+    let keep = step * cap as u64;
+    let items = VecDeque::with_capacity(cap.min(1024));
+    Self { step, keep, cap, items, bucket: None, sum: 0.0, maxq: VecDeque::new(), seq: 0 }
+  }
+
+  fn push(&mut self, ts: u64, value: f64) {
+    let idx = ts / self.step;
+
+    if let Some(b) = self.bucket.as_mut() {
+      if b.idx == idx {
+        b.ts = ts;
+        b.sum += value;
+        b.max = b.max.max(value);
+        b.count += 1;
+        return;
+      }
+
+      let point = b.point();
+      self.flush(point);
+    }
+
+    self.bucket = Some(Bucket { idx, ts, sum: value, max: value, count: 1 });
+  }
+
+  fn flush(&mut self, point: Point) {
+    if self.items.len() == self.cap {
+      let old = self.items.pop_front().unwrap();
+      self.sum -= old.avg;
+      let old_seq = self.seq - self.cap as u64;
+      if self.maxq.front().is_some_and(|x| x.0 == old_seq) {
+        self.maxq.pop_front();
+      }
+    }
+
+    while self.maxq.back().is_some_and(|x| x.1 <= point.max) {
+      self.maxq.pop_back();
+    }
+
+    self.maxq.push_back((self.seq, point.max));
+    self.sum += point.avg;
+    self.items.push_back(point);
+    self.seq += 1;
+  }
+
+  fn points(&self) -> impl DoubleEndedIterator<Item = Point> + '_ {
+    self.items.iter().copied().chain(self.bucket.map(|b| b.point()))
+  }
+
+  // First point at or after `ts`, None if `ts` is older than the tier retention
+  fn value_at(&self, ts: u64) -> Option<Point> {
+    let first = self.items.front().map(|x| x.ts).or(self.bucket.map(|b| b.ts))?;
+    if ts < first {
+      return None;
+    }
+
+    let idx = self.items.partition_point(|x| x.ts < ts);
+    match self.items.get(idx) {
+      Some(x) => Some(*x),
+      None => self.bucket.map(|b| b.point()).or(self.items.back().copied()),
+    }
+  }
+
+  fn avg(&self) -> f64 {
+    let (sum, count) = match self.bucket {
+      Some(b) => (self.sum + b.point().avg, self.items.len() + 1),
+      None => (self.sum, self.items.len()),
+    };
+
+    zero_div(sum, count as f64)
+  }
+
+  fn max(&self) -> f64 {
+    let max = self.maxq.front().map_or(0.0, |x| x.1);
+    self.bucket.map_or(max, |b| max.max(b.max))
+  }
+}
+
+// MARK: Series
+
+// Time series with multi-resolution downsampling: each value goes to every tier, first tier is
+// the most detailed one and is used for avg / max stats.
+#[derive(Debug)]
+pub struct Series {
+  tiers: Vec<Tier>,
+}
+
+impl Default for Series {
+  fn default() -> Self {
+    Self::new(&DEFAULT_TIERS)
+  }
+}
+
+impl Series {
+  pub fn new(specs: &[TierSpec]) -> Self {
+    let specs = if specs.is_empty() { &DEFAULT_TIERS[..] } else { specs };
+    Self { tiers: specs.iter().map(Tier::new).collect() }
+  }
+
+  pub fn push_at(&mut self, ts: u64, value: f64) {
+    for tier in self.tiers.iter_mut() {
+      tier.push(ts, value);
+    }
+  }
+
+  pub fn avg(&self) -> f64 {
+    self.tiers[0].avg()
+  }
+
+  pub fn max(&self) -> f64 {
+    self.tiers[0].max()
+  }
+
+  // Most detailed tier which retention covers the window (or the longest one)
+  pub fn tier_for(&self, window_ms: u64) -> usize {
+    let idx = self.tiers.iter().position(|x| x.keep >= window_ms);
+    idx.unwrap_or(self.tiers.len() - 1)
+  }
+
+  // Value at the given time from the most detailed tier which still has it
+  pub fn value_at(&self, ts: u64) -> Option<Point> {
+    self.tiers.iter().find_map(|x| x.value_at(ts))
+  }
+
+  // Points from oldest to newest, last one can be a not yet completed bucket
+  pub fn points(&self, tier: usize) -> impl DoubleEndedIterator<Item = Point> + '_ {
+    self.tiers.get(tier).into_iter().flat_map(|x| x.points())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn series(specs: &[(u64, u64)], values: &[(u64, f64)]) -> Series {
+    let specs = specs.iter().map(|&(step, keep)| TierSpec { step, keep }).collect::<Vec<_>>();
+    let mut series = Series::new(&specs);
+    for &(ts, value) in values {
+      series.push_at(ts, value);
+    }
+    series
+  }
+
+  fn avgs(series: &Series, tier: usize) -> Vec<f64> {
+    series.points(tier).map(|x| x.avg).collect()
+  }
+
+  #[test]
+  fn test_bucket_avg() {
+    let series = series(&[(1, 10)], &[(0, 1.0), (500, 3.0), (1200, 10.0)]);
+    let points = series.points(0).collect::<Vec<_>>();
+    assert_eq!(points.len(), 2);
+    assert_eq!((points[0].ts, points[0].avg, points[0].max), (500, 2.0, 3.0));
+    assert_eq!((points[1].ts, points[1].avg, points[1].max), (1200, 10.0, 10.0));
+    assert_eq!(series.avg(), 6.0);
+  }
+
+  #[test]
+  fn test_tier_rollover() {
+    let values = (0..6).map(|x| (x * 1000, x as f64)).collect::<Vec<_>>();
+    let series = series(&[(1, 3), (2, 10)], &values);
+
+    // oldest points leave the detailed tier, coarse tier still has them
+    assert_eq!(avgs(&series, 0), vec![2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(avgs(&series, 1), vec![0.5, 2.5, 4.5]);
+
+    assert_eq!(series.tier_for(3000), 0);
+    assert_eq!(series.tier_for(5000), 1);
+    assert_eq!(series.tier_for(60_000), 1);
+
+    assert_eq!(series.value_at(3000).map(|x| x.avg), Some(3.0));
+    assert_eq!(series.value_at(1000).map(|x| (x.ts, x.avg)), Some((1000, 0.5)));
+    assert!(series.value_at(0).is_none());
+  }
+
+  #[test]
+  fn test_max_eviction() {
+    let values = [(0, 9.0), (1000, 1.0), (2000, 4.0), (3000, 1.0)];
+    let mut series = series(&[(1, 3)], &values);
+    assert_eq!(series.max(), 9.0);
+
+    // 9 is evicted, 4 is still kept
+    series.push_at(4000, 0.5);
+    assert_eq!(series.max(), 4.0);
+    assert_eq!(series.avg(), (1.0 + 4.0 + 1.0 + 0.5) / 4.0);
+
+    series.push_at(5000, 0.5);
+    series.push_at(6000, 0.5);
+    assert_eq!(series.max(), 1.0);
+  }
+}
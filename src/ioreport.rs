@@ -0,0 +1,213 @@
+use serde::Serialize;
+
+use crate::sources::{
+  cfio_get_format, cfio_get_integer, cfio_get_residencies, cfio_watts, IOReport,
+  IOReportIteratorItem,
+};
+
+type WithError<T> = Result<T, Box<dyn std::error::Error>>;
+
+// MARK: Channels
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelFormat {
+  Simple,
+  State,
+  Histogram,
+  SimpleArray,
+  Unknown,
+}
+
+impl ChannelFormat {
+  fn from_raw(value: i32) -> Self {
+    match value {
+      1 => ChannelFormat::Simple,
+      2 => ChannelFormat::State,
+      3 => ChannelFormat::Histogram,
+      4 => ChannelFormat::SimpleArray,
+      _ => ChannelFormat::Unknown,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Channel {
+  pub group: String,
+  pub subgroup: String,
+  pub channel: String,
+  pub unit: String,
+  pub format: ChannelFormat,
+}
+
+impl Channel {
+  // "group :: subgroup :: channel (unit)", empty parts are skipped
+  fn name(&self) -> String {
+    let parts = [&self.group, &self.subgroup, &self.channel];
+    let name = parts.iter().filter(|x| !x.is_empty()).map(|x| x.as_str()).collect::<Vec<_>>();
+    match self.unit.is_empty() {
+      true => name.join(" :: "),
+      false => format!("{} ({})", name.join(" :: "), self.unit),
+    }
+  }
+}
+
+// MARK: Samples
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct State {
+  pub name: String,
+  pub residency: i64,
+  pub percent: f64, // of all states of the channel
+}
+
+fn states(items: Vec<(String, i64)>) -> Vec<State> {
+  let total = items.iter().map(|x| x.1).sum::<i64>();
+  let percent = |x: i64| if total > 0 { x as f64 / total as f64 * 100.0 } else { 0.0 };
+  items.into_iter().map(|(name, x)| State { name, residency: x, percent: percent(x) }).collect()
+}
+
+// Delta of one channel over the sample duration
+#[derive(Debug, Clone, Serialize)]
+pub struct Sample {
+  #[serde(flatten)]
+  pub channel: Channel,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub value: Option<i64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub watts: Option<f32>, // for energy channels
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub states: Option<Vec<State>>,
+}
+
+impl Sample {
+  fn value_text(&self) -> String {
+    if let Some(states) = &self.states {
+      let items = states.iter().map(|x| format!("{} {:.1}%", x.name, x.percent));
+      return items.collect::<Vec<_>>().join(" · ");
+    }
+
+    match (self.value, self.watts) {
+      (Some(value), Some(watts)) => format!("{} ({:.2}W)", value, watts),
+      (Some(value), None) => value.to_string(),
+      _ => format!("<{:?}>", self.channel.format).to_lowercase(),
+    }
+  }
+}
+
+// MARK: Commands
+
+fn channel(x: IOReportIteratorItem) -> Channel {
+  let format = ChannelFormat::from_raw(cfio_get_format(x.item));
+  Channel { group: x.group, subgroup: x.subgroup, channel: x.channel, unit: x.unit, format }
+}
+
+fn samples(ior: &IOReport, duration: u64) -> Vec<Sample> {
+  let mut items = Vec::new();
+
+  for x in ior.get_sample(duration) {
+    let item = x.item;
+    let mut sample = Sample { channel: channel(x), value: None, watts: None, states: None };
+    match sample.channel.format {
+      ChannelFormat::Simple => {
+        sample.value = Some(cfio_get_integer(item));
+        sample.watts = cfio_watts(item, &sample.channel.unit, duration).ok();
+      }
+      ChannelFormat::State => sample.states = Some(states(cfio_get_residencies(item))),
+      _ => {}
+    }
+
+    items.push(sample);
+  }
+
+  items
+}
+
+pub fn list(json: bool) -> WithError<()> {
+  let ior = IOReport::new(vec![])?;
+  let items = ior.get_channels().map(channel).collect::<Vec<_>>();
+
+  match json {
+    true => println!("{}", serde_json::to_string_pretty(&items)?),
+    false => {
+      for x in items {
+        println!("{} [{}]", x.name(), format!("{:?}", x.format).to_lowercase());
+      }
+    }
+  }
+
+  Ok(())
+}
+
+// All channels without group, duration in msec
+pub fn sample(
+  group: Option<&str>,
+  subgroup: Option<&str>,
+  duration: u64,
+  json: bool,
+) -> WithError<()> {
+  let channels_filter = match group {
+    Some(group) => vec![(group, subgroup)],
+    None => vec![],
+  };
+
+  let ior = IOReport::new(channels_filter)?;
+  let items = samples(&ior, duration.max(1));
+
+  match json {
+    true => println!("{}", serde_json::to_string_pretty(&items)?),
+    false => {
+      for x in items {
+        println!("{} = {}", x.channel.name(), x.value_text());
+      }
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn channel(subgroup: &str, unit: &str, format: ChannelFormat) -> Channel {
+    let (group, name) = ("CPU Stats".to_string(), "ECPU0".to_string());
+    Channel { group, subgroup: subgroup.to_string(), channel: name, unit: unit.to_string(), format }
+  }
+
+  #[test]
+  fn test_states() {
+    let items = states(vec![("IDLE".to_string(), 300), ("V0P5".to_string(), 100)]);
+    assert_eq!((items[0].percent, items[1].percent), (75.0, 25.0));
+    assert_eq!(states(vec![("IDLE".to_string(), 0)])[0].percent, 0.0);
+  }
+
+  #[test]
+  fn test_sample_text() {
+    let ch = channel("", "mJ", ChannelFormat::Simple);
+    assert_eq!(ch.name(), "CPU Stats :: ECPU0 (mJ)");
+
+    let sample = Sample { channel: ch, value: Some(1500), watts: Some(1.5), states: None };
+    assert_eq!(sample.value_text(), "1500 (1.50W)");
+
+    let ch = channel("CPU Core Performance States", "", ChannelFormat::State);
+    let states = states(vec![("IDLE".to_string(), 3), ("V0P5".to_string(), 1)]);
+    let sample = Sample { channel: ch, value: None, watts: None, states: Some(states) };
+    assert_eq!(sample.value_text(), "IDLE 75.0% · V0P5 25.0%");
+
+    let ch = channel("", "", ChannelFormat::Histogram);
+    let sample = Sample { channel: ch, value: None, watts: None, states: None };
+    assert_eq!(sample.value_text(), "<histogram>");
+  }
+
+  #[test]
+  fn test_sample_json() {
+    let ch = channel("", "nJ", ChannelFormat::Simple);
+    let sample = Sample { channel: ch, value: Some(10), watts: None, states: None };
+    let json = serde_json::to_value(&sample).unwrap();
+    assert_eq!(json["format"], "simple");
+    assert_eq!(json["channel"], "ECPU0");
+    assert_eq!(json["value"], 10);
+    assert!(json.get("states").is_none());
+  }
+}
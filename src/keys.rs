@@ -0,0 +1,297 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+// MARK: Actions
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+  Quit,
+  Help,
+  Theme,
+  View,
+  Window,
+  Pause,
+  CursorBack,
+  CursorForward,
+  Slower,
+  Faster,
+  Snapshot,
+  Profile,
+}
+
+impl Action {
+  pub const ALL: [Action; 12] = [
+    Action::Quit,
+    Action::Help,
+    Action::Theme,
+    Action::View,
+    Action::Window,
+    Action::Pause,
+    Action::CursorBack,
+    Action::CursorForward,
+    Action::Slower,
+    Action::Faster,
+    Action::Snapshot,
+    Action::Profile,
+  ];
+
+  // config key name
+  pub fn name(&self) -> &'static str {
+    match self {
+      Action::Quit => "quit",
+      Action::Help => "help",
+      Action::Theme => "theme",
+      Action::View => "view",
+      Action::Window => "window",
+      Action::Pause => "pause",
+      Action::CursorBack => "cursor_back",
+      Action::CursorForward => "cursor_forward",
+      Action::Slower => "slower",
+      Action::Faster => "faster",
+      Action::Snapshot => "snapshot",
+      Action::Profile => "profile",
+    }
+  }
+
+  pub fn description(&self) -> &'static str {
+    match self {
+      Action::Quit => "Quit",
+      Action::Help => "Show / hide this help",
+      Action::Theme => "Change color theme",
+      Action::View => "Switch view: sparkline / gauge / chart",
+      Action::Window => "Change chart time window",
+      Action::Pause => "Pause / resume display",
+      Action::CursorBack => "Move cursor back in history",
+      Action::CursorForward => "Move cursor forward in history",
+      Action::Slower => "Increase update interval",
+      Action::Faster => "Decrease update interval",
+      Action::Snapshot => "Save screen to file",
+      Action::Profile => "Switch config profile",
+    }
+  }
+
+  fn default_keys(&self) -> &'static [&'static str] {
+    match self {
+      Action::Quit => &["q"],
+      Action::Help => &["?"],
+      Action::Theme => &["c"],
+      Action::View => &["v"],
+      Action::Window => &["w"],
+      Action::Pause => &["p", "space"],
+      Action::CursorBack => &["left"],
+      Action::CursorForward => &["right"],
+      Action::Slower => &["+", "="],
+      Action::Faster => &["-"],
+      Action::Snapshot => &["s"],
+      Action::Profile => &["P"],
+    }
+  }
+}
+
+// MARK: Keys
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+  code: KeyCode,
+  mods: KeyModifiers, // only ctrl and alt, shift is part of the char
+}
+
+// Ctrl-C always quits, so it can't be bound to other actions
+const CTRL_C: Key = Key { code: KeyCode::Char('c'), mods: KeyModifiers::CONTROL };
+
+impl Key {
+  // Formats: "q", "?", "space", "left", "f1", "ctrl+x", "alt+left"
+  pub fn parse(text: &str) -> Result<Self, String> {
+    let mut mods = KeyModifiers::NONE;
+    let mut rest = text;
+
+    loop {
+      let lower = rest.to_lowercase();
+      if lower.starts_with("ctrl+") && rest.len() > 5 {
+        mods |= KeyModifiers::CONTROL;
+        rest = &rest[5..];
+      } else if lower.starts_with("alt+") && rest.len() > 4 {
+        mods |= KeyModifiers::ALT;
+        rest = &rest[4..];
+      } else {
+        break;
+      }
+    }
+
+    let code = match rest.to_lowercase().as_str() {
+      "space" => KeyCode::Char(' '),
+      "left" => KeyCode::Left,
+      "right" => KeyCode::Right,
+      "up" => KeyCode::Up,
+      "down" => KeyCode::Down,
+      "enter" => KeyCode::Enter,
+      "tab" => KeyCode::Tab,
+      "backspace" => KeyCode::Backspace,
+      "home" => KeyCode::Home,
+      "end" => KeyCode::End,
+      "pageup" => KeyCode::PageUp,
+      "pagedown" => KeyCode::PageDown,
+      x if x.len() > 1 && x.starts_with('f') => match x[1..].parse::<u8>() {
+        Ok(n) if (1..=12).contains(&n) => KeyCode::F(n),
+        _ => return Err(format!("unknown key '{}'", text)),
+      },
+      _ => {
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next()) {
+          (Some(c), None) => KeyCode::Char(c),
+          _ => return Err(format!("unknown key '{}'", text)),
+        }
+      }
+    };
+
+    Ok(Self { code, mods })
+  }
+
+  fn from_event(event: &KeyEvent) -> Self {
+    let mods = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+    Self { code: event.code, mods }
+  }
+}
+
+impl fmt::Display for Key {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.mods.contains(KeyModifiers::CONTROL) {
+      write!(f, "ctrl+")?;
+    }
+    if self.mods.contains(KeyModifiers::ALT) {
+      write!(f, "alt+")?;
+    }
+
+    match self.code {
+      KeyCode::Char(' ') => write!(f, "space"),
+      KeyCode::Char(c) => write!(f, "{}", c),
+      KeyCode::Left => write!(f, "←"),
+      KeyCode::Right => write!(f, "→"),
+      KeyCode::Up => write!(f, "↑"),
+      KeyCode::Down => write!(f, "↓"),
+      KeyCode::F(n) => write!(f, "F{}", n),
+      x => write!(f, "{}", format!("{:?}", x).to_lowercase()),
+    }
+  }
+}
+
+// MARK: KeyMap
+
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+  items: Vec<(Key, Action)>,
+}
+
+impl Default for KeyMap {
+  fn default() -> Self {
+    Self::new(&BTreeMap::new()).unwrap()
+  }
+}
+
+impl KeyMap {
+  // Defaults with actions from config replaced, fails on unknown or conflicting keys
+  pub fn new(user: &BTreeMap<Action, Vec<String>>) -> Result<Self, String> {
+    let mut items: Vec<(Key, Action)> = Vec::new();
+
+    for action in Action::ALL {
+      let keys = match user.get(&action) {
+        Some(keys) => keys.iter().map(|x| x.as_str()).collect::<Vec<_>>(),
+        None => action.default_keys().to_vec(),
+      };
+
+      for text in keys {
+        let key = Key::parse(text).map_err(|e| format!("keys.{}: {}", action.name(), e))?;
+        if key == CTRL_C && action != Action::Quit {
+          return Err(format!("keys.{}: '{}' is reserved for quit", action.name(), key));
+        }
+
+        if let Some((_, other)) = items.iter().find(|x| x.0 == key && x.1 != action) {
+          let (a, b) = (other.name(), action.name());
+          return Err(format!("keys: '{}' is bound to both '{}' and '{}'", key, a, b));
+        }
+
+        items.push((key, action));
+      }
+    }
+
+    Ok(Self { items })
+  }
+
+  pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+    let key = Key::from_event(event);
+    if key == CTRL_C {
+      return Some(Action::Quit);
+    }
+
+    self.items.iter().find(|x| x.0 == key).map(|x| x.1)
+  }
+
+  pub fn keys(&self, action: Action) -> Vec<Key> {
+    self.items.iter().filter(|x| x.1 == action).map(|x| x.0).collect()
+  }
+
+  // First key of action for short hints, e.g. "q"
+  pub fn hint(&self, action: Action) -> String {
+    self.keys(action).first().map_or("-".to_string(), |x| x.to_string())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn press(code: KeyCode, mods: KeyModifiers) -> KeyEvent {
+    KeyEvent::new(code, mods)
+  }
+
+  #[test]
+  fn test_parse_keys() {
+    assert_eq!(Key::parse("q").unwrap().code, KeyCode::Char('q'));
+    assert_eq!(Key::parse("space").unwrap().code, KeyCode::Char(' '));
+    assert_eq!(Key::parse("F5").unwrap().code, KeyCode::F(5));
+
+    let key = Key::parse("ctrl+left").unwrap();
+    assert_eq!((key.code, key.mods), (KeyCode::Left, KeyModifiers::CONTROL));
+    assert_eq!(key.to_string(), "ctrl+←");
+
+    assert!(Key::parse("qq").is_err());
+    assert!(Key::parse("f13").is_err());
+  }
+
+  #[test]
+  fn test_default_map() {
+    let keys = KeyMap::default();
+    assert_eq!(keys.action(&press(KeyCode::Char('v'), KeyModifiers::NONE)), Some(Action::View));
+    assert_eq!(keys.action(&press(KeyCode::Char('?'), KeyModifiers::SHIFT)), Some(Action::Help));
+    assert_eq!(keys.action(&press(KeyCode::Char('c'), KeyModifiers::CONTROL)), Some(Action::Quit));
+    assert_eq!(keys.action(&press(KeyCode::Char('x'), KeyModifiers::NONE)), None);
+  }
+
+  #[test]
+  fn test_remap() {
+    let user = BTreeMap::from([(Action::View, vec!["x".to_string(), "tab".to_string()])]);
+    let keys = KeyMap::new(&user).unwrap();
+
+    assert_eq!(keys.action(&press(KeyCode::Char('x'), KeyModifiers::NONE)), Some(Action::View));
+    assert_eq!(keys.action(&press(KeyCode::Tab, KeyModifiers::NONE)), Some(Action::View));
+    assert_eq!(keys.action(&press(KeyCode::Char('v'), KeyModifiers::NONE)), None);
+    assert_eq!(keys.hint(Action::View), "x");
+  }
+
+  #[test]
+  fn test_conflicts() {
+    let user = BTreeMap::from([(Action::View, vec!["c".to_string()])]);
+    let err = KeyMap::new(&user).unwrap_err();
+    assert_eq!(err, "keys: 'c' is bound to both 'theme' and 'view'");
+
+    let user = BTreeMap::from([(Action::Pause, vec!["ctrl+c".to_string()])]);
+    assert!(KeyMap::new(&user).is_err());
+
+    let user = BTreeMap::from([(Action::Help, vec!["hlp".to_string()])]);
+    assert_eq!(KeyMap::new(&user).unwrap_err(), "keys.help: unknown key 'hlp'");
+  }
+}
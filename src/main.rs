@@ -1,21 +1,137 @@
+mod alerts;
 mod app;
 mod config;
 mod debug;
+mod fans;
+mod history;
+mod ioreport;
+mod keys;
 mod metrics;
+mod raw;
+mod sensors;
+mod smc;
+mod snapshot;
 mod sources;
+mod summary;
+mod theme;
+mod units;
 
-use app::App;
-use clap::{Parser, Subcommand};
+use app::{App, Compact};
+use clap::{Args, Parser, Subcommand};
+use config::{Config, Overrides, ViewType, DEFAULT_INTERVAL, MIN_INTERVAL};
 use metrics::Sampler;
 use std::error::Error;
 
 #[derive(Debug, Subcommand)]
 enum Commands {
   /// Print raw metrics data instead of TUI
-  Raw,
+  Raw {
+    /// Output format, `text` and `csv` use display units from config
+    #[arg(long, value_enum, default_value_t)]
+    format: raw::RawFormat,
+  },
 
   /// Print raw metrics data instead of TUI
   Debug,
+
+  /// Explore SMC keys
+  Smc {
+    #[command(subcommand)]
+    command: SmcCommand,
+  },
+
+  /// Explore IOReport channels
+  Ioreport {
+    #[command(subcommand)]
+    command: IoreportCommand,
+  },
+
+  /// List temperature sensors with values and CPU/GPU classification
+  Sensors {
+    /// Print min / avg / max and hottest sensor per group instead of every sensor
+    #[arg(long)]
+    groups: bool,
+
+    /// Number of readings (`--interval` apart) to detect stuck and jumping sensors
+    #[arg(short = 'n', long, default_value_t = 1)]
+    samples: usize,
+
+    #[command(flatten)]
+    output: Output,
+  },
+}
+
+#[derive(Debug, Args)]
+struct Output {
+  /// Print as JSON
+  #[arg(long)]
+  json: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum SmcCommand {
+  /// List all keys with types, sizes and decoded values
+  List {
+    /// Only keys starting with this prefix, e.g. "T"
+    #[arg(long)]
+    prefix: Option<String>,
+
+    /// Only keys of this type, e.g. "flt" or "ui32"
+    #[arg(long = "type", value_name = "TYPE")]
+    data_type: Option<String>,
+
+    #[command(flatten)]
+    output: Output,
+  },
+
+  /// Read values of keys
+  Read {
+    #[arg(required = true)]
+    keys: Vec<String>,
+
+    #[command(flatten)]
+    output: Output,
+  },
+
+  /// Print values of keys periodically
+  Watch {
+    #[arg(required = true)]
+    keys: Vec<String>,
+
+    /// Update interval in milliseconds
+    #[arg(short, long, default_value_t = DEFAULT_INTERVAL)]
+    interval: u64,
+
+    #[command(flatten)]
+    output: Output,
+  },
+}
+
+#[derive(Debug, Subcommand)]
+enum IoreportCommand {
+  /// List all channels with units and formats
+  List {
+    #[command(flatten)]
+    output: Output,
+  },
+
+  /// Print decoded deltas of channels over a duration
+  Sample {
+    /// Channel group, e.g. "Energy Model"; all channels if not set
+    #[arg(long)]
+    group: Option<String>,
+
+    /// Channel subgroup, e.g. "CPU Core Performance States"
+    #[arg(long, requires = "group")]
+    subgroup: Option<String>,
+
+    /// Sample duration in milliseconds
+    #[arg(short, long, default_value_t = 1000)]
+    duration: u64,
+
+    #[command(flatten)]
+    output: Output,
+  },
 }
 
 /// Sudoless performance monitoring CLI tool for Apple Silicon processors
@@ -26,27 +142,97 @@ struct Cli {
   #[command(subcommand)]
   command: Option<Commands>,
 
-  /// Update interval in milliseconds
-  #[arg(short, long, default_value_t = 1000)]
-  interval: u64,
+  /// Config file [default: $XDG_CONFIG_HOME/macmon.json or ~/.config/macmon.json]
+  #[arg(short, long, value_name = "FILE")]
+  config: Option<String>,
+
+  /// Config profile to use [default: from config]
+  #[arg(short, long)]
+  profile: Option<String>,
+
+  /// Update interval in milliseconds [default: from config or 1000]
+  #[arg(short, long)]
+  interval: Option<u64>,
+
+  /// Color theme [default: from config]
+  #[arg(long)]
+  theme: Option<String>,
+
+  /// Charts view [default: from config]
+  #[arg(long, value_enum)]
+  view: Option<ViewType>,
+
+  /// ASCII-only borders and bars
+  #[arg(long)]
+  ascii: bool,
+
+  /// Compact render mode [default: chosen by terminal size]
+  #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "mini")]
+  compact: Option<Compact>,
+
+  /// Print one frame at the terminal size and exit
+  #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "text")]
+  snapshot: Option<snapshot::Format>,
+
+  /// Write session summary as JSON to this file on exit
+  #[arg(long, value_name = "FILE")]
+  summary_json: Option<String>,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
   let args = Cli::parse();
-  let msec = args.interval.max(100);
-
   match &args.command {
-    Some(Commands::Raw) => {
+    Some(Commands::Raw { format }) => {
+      let msec = args.interval.unwrap_or(DEFAULT_INTERVAL).max(MIN_INTERVAL);
+      let cfg = Config::load(args.config.as_deref());
       let mut sampler = Sampler::new()?;
-
-      loop {
-        println!("{:?}", sampler.get_metrics(msec)?);
-      }
+      sampler.set_sensors(&cfg.sensors);
+      raw::print(&mut sampler, msec, *format, cfg.units, &cfg.sensors)?
     }
     Some(Commands::Debug) => debug::print_debug()?,
+    Some(Commands::Smc { command }) => match command {
+      SmcCommand::List { prefix, data_type, output } => {
+        smc::list(prefix.as_deref(), data_type.as_deref(), output.json)?
+      }
+      SmcCommand::Read { keys, output } => smc::read(keys, output.json)?,
+      SmcCommand::Watch { keys, interval, output } => smc::watch(keys, *interval, output.json)?,
+    },
+    Some(Commands::Ioreport { command }) => match command {
+      IoreportCommand::List { output } => ioreport::list(output.json)?,
+      IoreportCommand::Sample { group, subgroup, duration, output } => {
+        ioreport::sample(group.as_deref(), subgroup.as_deref(), *duration, output.json)?
+      }
+    },
+    Some(Commands::Sensors { groups, samples, output }) => {
+      let cfg = Config::load(args.config.as_deref());
+      let msec = args.interval.unwrap_or(DEFAULT_INTERVAL).max(MIN_INTERVAL);
+      sensors::list(&cfg.sensors, *samples, msec, *groups, output.json)?
+    }
     _ => {
-      let mut app = App::new()?;
-      app.run_loop(msec)?;
+      // CLI values are used for this session, but not saved to the file
+      let mut cfg = Config::load(args.config.as_deref());
+      cfg.apply_overrides(Overrides {
+        profile: args.profile,
+        interval: args.interval,
+        theme: args.theme,
+        view_type: args.view,
+        ascii: args.ascii,
+      });
+
+      for msg in &cfg.warnings {
+        eprintln!("{}", msg);
+      }
+
+      let mut app = App::new(cfg)?;
+      app.set_compact(args.compact);
+
+      if let Some(format) = args.snapshot {
+        print!("{}", app.snapshot(format)?);
+        return Ok(());
+      }
+
+      app.set_summary_file(args.summary_json);
+      app.run_loop()?;
     }
   }
 
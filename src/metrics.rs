@@ -1,5 +1,14 @@
+use std::collections::BTreeMap;
+
 use core_foundation::dictionary::CFDictionaryRef;
+use serde::Serialize;
 
+use crate::fans::{fan_count, read_fans, FanMetrics};
+use crate::history::now_ms;
+use crate::sensors::{
+  glob, group_stats, is_float, mark_unused, Mapping, Sensor, SensorFilter, SensorsConfig, Source,
+  CPU, GPU, OTHER,
+};
 use crate::sources::{
   cfio_get_residencies, cfio_watts, libc_ram, libc_swap, IOHIDSensors, IOReport, SocInfo, SMC,
 };
@@ -10,12 +19,32 @@ type WithError<T> = Result<T, Box<dyn std::error::Error>>;
 const CPU_FREQ_CORE_SUBG: &str = "CPU Core Performance States";
 const GPU_FREQ_DICE_SUBG: &str = "GPU Performance States";
 
+// Per-cluster / per-core Energy Model channels, already counted in "CPU Energy" and "GPU Energy"
+const ENERGY_PARTS: [&str; 6] = ["ECPU*", "PCPU*", "EACC_CPU*", "PACC*_CPU*", "GPU", "GPU?"];
+
 // MARK: Structs
 
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct TempGroup {
+  pub min: f32,        // Celsius
+  pub avg: f32,        // Celsius
+  pub max: f32,        // Celsius
+  pub hotspot: String, // name of the hottest sensor
+  pub count: usize,
+}
+
 #[derive(Debug, Default)]
 pub struct TempMetrics {
-  pub cpu_temp_avg: f32, // Celsius
-  pub gpu_temp_avg: f32, // Celsius
+  pub cpu_temp_avg: Option<f32>, // Celsius, None if every sensor of the group is rejected
+  pub gpu_temp_avg: Option<f32>, // Celsius
+  pub groups: BTreeMap<String, TempGroup>, // all sensor groups, including cpu / gpu
+}
+
+impl TempMetrics {
+  // Custom sensor groups (ssd, battery, ...)
+  pub fn custom_groups(&self) -> impl Iterator<Item = (&String, &TempGroup)> {
+    self.groups.iter().filter(|x| x.0 != CPU && x.0 != GPU)
+  }
 }
 
 #[derive(Debug, Default)]
@@ -26,18 +55,35 @@ pub struct MemMetrics {
   pub swap_usage: u64, // bytes
 }
 
+// Every field is optional: `None` means the source behind it is not available on this machine
+// (e.g. locked-down SMC or missing IOReport group) or failed on this sample.
 #[derive(Debug, Default)]
 pub struct Metrics {
-  pub temp: TempMetrics,
-  pub memory: MemMetrics,
-  pub ecpu_usage: (u32, f32), // freq, percent_from_max
-  pub pcpu_usage: (u32, f32), // freq, percent_from_max
-  pub gpu_usage: (u32, f32),  // freq, percent_from_max
-  pub cpu_power: f32,         // Watts
-  pub gpu_power: f32,         // Watts
-  pub ane_power: f32,         // Watts
-  pub all_power: f32,         // Watts
-  pub sys_power: f32,         // Watts
+  pub temp: Option<TempMetrics>,
+  pub memory: Option<MemMetrics>,
+  pub ecpu_usage: Option<(u32, f32)>, // freq, percent_from_max
+  pub pcpu_usage: Option<(u32, f32)>, // freq, percent_from_max
+  pub gpu_usage: Option<(u32, f32)>,  // freq, percent_from_max
+  pub cores: Option<Vec<(String, u32, f32)>>, // ECPU0 / PCPU1 / ..., freq, percent_from_max
+  pub cpu_power: Option<f32>,         // Watts
+  pub gpu_power: Option<f32>,         // Watts
+  pub ane_power: Option<f32>,         // Watts
+  pub all_power: Option<f32>,         // Watts
+  pub other_power: Option<f32>, // Watts, rest of Energy Model channels (DRAM, display, media, ...)
+  pub sys_power: Option<f32>,   // Watts
+  pub fans: Option<Vec<FanMetrics>>, // None on fanless machines
+}
+
+#[derive(Debug, Default)]
+struct IORMetrics {
+  ecpu_usage: (u32, f32),
+  pcpu_usage: (u32, f32),
+  gpu_usage: (u32, f32),
+  cores: Vec<(String, u32, f32)>,
+  cpu_power: f32,
+  gpu_power: f32,
+  ane_power: f32,
+  other_power: f32,
 }
 
 // MARK: Helpers
@@ -47,13 +93,18 @@ pub fn zero_div<T: core::ops::Div<Output = T> + Default + PartialEq>(a: T, b: T)
   return if b == zero { zero } else { a / b };
 }
 
-fn calc_freq(item: CFDictionaryRef, freqs: &Vec<u32>) -> (u32, f32) {
+// None if residencies don't match the frequency table, the sample is skipped then
+fn calc_freq(item: CFDictionaryRef, freqs: &Vec<u32>) -> Option<(u32, f32)> {
   let items = cfio_get_residencies(item); // (ns, freq)
-  let (len1, len2) = (items.len(), freqs.len());
-  assert!(len1 > len2, "cacl_freq invalid data: {} vs {}", len1, len2); // todo?
+  if freqs.is_empty() || items.len() <= freqs.len() {
+    return None;
+  }
 
   // IDLE / DOWN for CPU; OFF for GPU; DOWN only on M2?/M3 Max Chips
-  let offset = items.iter().position(|x| x.0 != "IDLE" && x.0 != "DOWN" && x.0 != "OFF").unwrap();
+  let offset = items.iter().position(|x| x.0 != "IDLE" && x.0 != "DOWN" && x.0 != "OFF")?;
+  if items.len() < offset + freqs.len() {
+    return None;
+  }
 
   let usage = items.iter().map(|x| x.1 as f64).skip(offset).sum::<f64>();
   let total = items.iter().map(|x| x.1 as f64).sum::<f64>();
@@ -70,62 +121,64 @@ fn calc_freq(item: CFDictionaryRef, freqs: &Vec<u32>) -> (u32, f32) {
   let max_freq = freqs.last().unwrap().clone() as f64;
   let from_max = (avg_freq.max(min_freq) * usage_ratio) / max_freq;
 
-  (avg_freq as u32, from_max as f32)
+  Some((avg_freq as u32, from_max as f32))
 }
 
 fn calc_freq_final(items: &Vec<(u32, f32)>, freqs: &Vec<u32>) -> (u32, f32) {
   let avg_freq = zero_div(items.iter().map(|x| x.0 as f32).sum(), items.len() as f32);
   let avg_perc = zero_div(items.iter().map(|x| x.1 as f32).sum(), items.len() as f32);
-  let min_freq = freqs.first().unwrap().clone() as f32;
+  let min_freq = freqs.first().copied().unwrap_or(0) as f32;
 
   (avg_freq.max(min_freq) as u32, avg_perc)
 }
 
-fn init_smc() -> WithError<(SMC, Vec<String>, Vec<String>)> {
-  let mut smc = SMC::new()?;
-
-  let mut cpu_sensors = Vec::new();
-  let mut gpu_sensors = Vec::new();
-
-  let names = smc.read_all_keys().unwrap_or(vec![]);
-  for name in &names {
-    let key = match smc.read_key_info(&name) {
-      Ok(key) => key,
-      Err(_) => continue,
-    };
-
-    if key.data_size != 4 || key.data_type != 1718383648 {
-      continue;
+// Average of every core over several samples, in order of IOReport channels
+fn calc_cores_final(items: &[IORMetrics]) -> Vec<(String, u32, f32)> {
+  let mut rs: Vec<(String, u64, f32, u32)> = Vec::new(); // name, sum of freqs, sum of usage, count
+  for (name, freq, usage) in items.iter().flat_map(|x| x.cores.iter()) {
+    match rs.iter_mut().find(|x| x.0 == *name) {
+      Some(x) => {
+        x.1 += *freq as u64;
+        x.2 += usage;
+        x.3 += 1;
+      }
+      None => rs.push((name.clone(), *freq as u64, *usage, 1)),
     }
+  }
 
-    let _ = match smc.read_val(&name) {
-      Ok(val) => val,
-      Err(_) => continue,
-    };
+  rs.into_iter()
+    .map(|(name, freq, usage, n)| (name, (freq / n as u64) as u32, usage / n as f32))
+    .collect()
+}
 
-    // Unfortunately, it is not known which keys are responsible for what.
-    // Basically in the code that can be found publicly "Tp" is used for CPU and "Tg" for GPU.
+// All float "T*" keys, groups are chosen by sensors mapping (see `macmon sensors`)
+fn init_smc() -> WithError<(SMC, Vec<String>)> {
+  let mut smc = SMC::new()?;
+  let mut sensors = Vec::new();
 
-    match name {
-      name if name.starts_with("Tp") => cpu_sensors.push(name.clone()),
-      name if name.starts_with("Tg") => gpu_sensors.push(name.clone()),
-      _ => (),
+  let names = smc.read_all_keys().unwrap_or(vec![]);
+  for name in names.iter().filter(|x| x.starts_with('T')) {
+    match smc.read_val(name) {
+      Ok(val) if is_float(&val.unit, val.data.len()) => sensors.push(name.clone()),
+      _ => continue,
     }
   }
 
-  // println!("{} {}", cpu_sensors.len(), gpu_sensors.len());
-  Ok((smc, cpu_sensors, gpu_sensors))
+  Ok((smc, sensors))
 }
 
 // MARK: Sampler
 
 pub struct Sampler {
   soc: SocInfo,
-  ior: IOReport,
-  hid: IOHIDSensors,
-  smc: SMC,
-  smc_cpu_keys: Vec<String>,
-  smc_gpu_keys: Vec<String>,
+  ior: Option<IOReport>,
+  hid: Option<IOHIDSensors>,
+  smc: Option<SMC>,
+  smc_keys: Vec<String>, // float temperature keys
+  mapping: Mapping,
+  smc_grouped: Vec<String>, // keys in any group of the mapping, read on each sample
+  filter: SensorFilter,
+  fan_count: usize,
 }
 
 impl Sampler {
@@ -137,67 +190,73 @@ impl Sampler {
       ("GPU Stats", Some(GPU_FREQ_DICE_SUBG)), // gpu freq
     ];
 
+    // SocInfo is required to interpret frequencies, other sources are optional
     let soc = SocInfo::new()?;
-    let ior = IOReport::new(channels)?;
-    let hid = IOHIDSensors::new()?;
-    let (smc, smc_cpu_keys, smc_gpu_keys) = init_smc()?;
-
-    Ok(Sampler { soc, ior, hid, smc, smc_cpu_keys, smc_gpu_keys })
+    let ior = IOReport::new(channels).ok();
+    let hid = IOHIDSensors::new().ok();
+    let (mut smc, smc_keys) = match init_smc() {
+      Ok((smc, keys)) => (Some(smc), keys),
+      Err(_) => (None, vec![]),
+    };
+    let fan_count = smc.as_mut().map_or(0, fan_count);
+
+    let (mapping, filter) = (Mapping::default(), SensorFilter::default());
+    let smc_grouped = vec![];
+    let mut sampler =
+      Sampler { soc, ior, hid, smc, smc_keys, mapping, smc_grouped, filter, fan_count };
+    sampler.set_sensors(&SensorsConfig::default());
+    Ok(sampler)
   }
 
-  fn get_temp_smc(&mut self) -> WithError<TempMetrics> {
-    let mut cpu_metrics = Vec::new();
-    for sensor in &self.smc_cpu_keys {
-      let val = self.smc.read_val(sensor)?;
-      let val = f32::from_le_bytes(val.data[0..4].try_into().unwrap());
-      cpu_metrics.push(val);
-    }
-
-    let mut gpu_metrics = Vec::new();
-    for sensor in &self.smc_gpu_keys {
-      let val = self.smc.read_val(sensor)?;
-      let val = f32::from_le_bytes(val.data[0..4].try_into().unwrap());
-      gpu_metrics.push(val);
-    }
-
-    let cpu_temp_avg = zero_div(cpu_metrics.iter().sum::<f32>(), cpu_metrics.len() as f32);
-    let gpu_temp_avg = zero_div(gpu_metrics.iter().sum::<f32>(), gpu_metrics.len() as f32);
-
-    Ok(TempMetrics { cpu_temp_avg, gpu_temp_avg })
+  // Sensor groups from config, applied from the next sample
+  pub fn set_sensors(&mut self, cfg: &SensorsConfig) {
+    let mapping = Mapping::new(cfg);
+    let keys = self.smc_keys.iter().filter(|x| mapping.classify(Source::Smc, x).0 != OTHER);
+    self.smc_grouped = keys.cloned().collect();
+    self.mapping = mapping;
+    self.filter = SensorFilter::new(cfg.filter);
   }
 
-  fn get_temp_hid(&mut self) -> WithError<TempMetrics> {
-    let metrics = self.hid.get_metrics();
-
-    let mut cpu_values = Vec::new();
-    let mut gpu_values = Vec::new();
+  fn get_temp(&mut self) -> WithError<TempMetrics> {
+    let mut items = Vec::new();
 
-    for (name, value) in &metrics {
-      if name.starts_with("pACC MTR Temp Sensor") || name.starts_with("eACC MTR Temp Sensor") {
-        // println!("{}: {}", name, value);
-        cpu_values.push(*value);
-        continue;
+    if let Some(smc) = self.smc.as_mut() {
+      // unreadable keys are skipped, other sensors are still reported
+      for key in &self.smc_grouped {
+        if let Ok(val) = smc.read_val(key) {
+          items.push(Sensor::smc(key, &val.unit, &val.data, &self.mapping));
+        }
       }
+    }
 
-      if name.starts_with("GPU MTR Temp Sensor") {
-        // println!("{}: {}", name, value);
-        gpu_values.push(*value);
-        continue;
+    // HID for M1, SMC for M2/M3
+    // UPD: Looks like HID/SMC related to OS version, not to the chip (SMC available from macOS 14)
+    let smc_cpu = items.iter().any(|x| x.category == CPU);
+    if let Some(hid) = self.hid.as_ref().filter(|_| !smc_cpu || self.mapping.custom_hid()) {
+      for (name, value) in hid.get_metrics() {
+        items.push(Sensor::hid(&name, value, &self.mapping));
       }
     }
 
-    let cpu_temp_avg = zero_div(cpu_values.iter().sum(), cpu_values.len() as f32);
-    let gpu_temp_avg = zero_div(gpu_values.iter().sum(), gpu_values.len() as f32);
+    mark_unused(&mut items);
+    self.filter.apply(&mut items, now_ms());
+    let groups = group_stats(&items);
+    if groups.is_empty() {
+      return Err("No temperature sensors".into());
+    }
 
-    Ok(TempMetrics { cpu_temp_avg, gpu_temp_avg })
+    let cpu_temp_avg = groups.get(CPU).map(|x| x.avg);
+    let gpu_temp_avg = groups.get(GPU).map(|x| x.avg);
+    Ok(TempMetrics { cpu_temp_avg, gpu_temp_avg, groups })
   }
 
-  fn get_temp(&mut self) -> WithError<TempMetrics> {
-    // HID for M1, SMC for M2/M3
-    // UPD: Looks like HID/SMC related to OS version, not to the chip (SMC available from macOS 14)
-    match self.smc_cpu_keys.len() > 0 {
-      true => self.get_temp_smc(),
-      false => self.get_temp_hid(),
+  fn get_fans(&mut self) -> Option<Vec<FanMetrics>> {
+    let smc = self.smc.as_mut()?;
+    let fans = read_fans(smc, self.fan_count);
+    if fans.is_empty() {
+      None
+    } else {
+      Some(fans)
     }
   }
 
@@ -208,38 +267,49 @@ impl Sampler {
   }
 
   fn get_sys_power(&mut self) -> WithError<f32> {
-    let val = self.smc.read_val("PSTR")?;
+    let smc = self.smc.as_mut().ok_or("SMC not available")?;
+    let val = smc.read_val("PSTR")?;
     let val = f32::from_le_bytes(val.data.clone().try_into().unwrap());
     Ok(val)
   }
 
-  pub fn get_metrics(&mut self, duration: u64) -> WithError<Metrics> {
+  fn get_ior_metrics(&mut self, duration: u64) -> WithError<IORMetrics> {
+    let ior = self.ior.as_mut().ok_or("IOReport not available")?;
     let measures: usize = 4;
-    let mut results: Vec<Metrics> = Vec::with_capacity(measures);
+    let mut results: Vec<IORMetrics> = Vec::with_capacity(measures);
 
     // do several samples to smooth metrics
     // see: https://github.com/vladkens/macmon/issues/10
-    for (sample, sample_dt) in self.ior.get_samples(duration, measures) {
+    for (sample, sample_dt) in ior.get_samples(duration, measures) {
       let mut ecpu_usages = Vec::new();
       let mut pcpu_usages = Vec::new();
-      let mut rs = Metrics::default();
+      let mut rs = IORMetrics::default();
 
       for x in sample {
         if x.group == "CPU Stats" && x.subgroup == CPU_FREQ_CORE_SUBG {
           if x.channel.contains("ECPU") {
-            ecpu_usages.push(calc_freq(x.item, &self.soc.ecpu_freqs));
+            if let Some(usage) = calc_freq(x.item, &self.soc.ecpu_freqs) {
+              rs.cores.push((x.channel.clone(), usage.0, usage.1));
+              ecpu_usages.push(usage);
+            }
             continue;
           }
 
           if x.channel.contains("PCPU") {
-            pcpu_usages.push(calc_freq(x.item, &self.soc.pcpu_freqs));
+            if let Some(usage) = calc_freq(x.item, &self.soc.pcpu_freqs) {
+              rs.cores.push((x.channel.clone(), usage.0, usage.1));
+              pcpu_usages.push(usage);
+            }
             continue;
           }
         }
 
         if x.group == "GPU Stats" && x.subgroup == GPU_FREQ_DICE_SUBG {
           match x.channel.as_str() {
-            "GPUPH" => rs.gpu_usage = calc_freq(x.item, &self.soc.gpu_freqs[1..].to_vec()),
+            "GPUPH" => {
+              let freqs = self.soc.gpu_freqs.get(1..).unwrap_or_default().to_vec();
+              rs.gpu_usage = calc_freq(x.item, &freqs).unwrap_or_default();
+            }
             _ => {}
           }
         }
@@ -249,7 +319,13 @@ impl Sampler {
             "CPU Energy" => rs.cpu_power += cfio_watts(x.item, &x.unit, sample_dt)?,
             "GPU Energy" => rs.gpu_power += cfio_watts(x.item, &x.unit, sample_dt)?,
             c if c.starts_with("ANE") => rs.ane_power += cfio_watts(x.item, &x.unit, sample_dt)?,
-            _ => {}
+            c if ENERGY_PARTS.iter().any(|p| glob(p, c)) => {}
+            // channels with non-energy units are skipped
+            _ => {
+              if let Ok(watts) = cfio_watts(x.item, &x.unit, sample_dt) {
+                rs.other_power += watts;
+              }
+            }
           }
         }
       }
@@ -259,7 +335,7 @@ impl Sampler {
       results.push(rs);
     }
 
-    let mut rs = Metrics::default();
+    let mut rs = IORMetrics { cores: calc_cores_final(&results), ..Default::default() };
     rs.ecpu_usage.0 = zero_div(results.iter().map(|x| x.ecpu_usage.0).sum(), measures as _);
     rs.ecpu_usage.1 = zero_div(results.iter().map(|x| x.ecpu_usage.1).sum(), measures as _);
     rs.pcpu_usage.0 = zero_div(results.iter().map(|x| x.pcpu_usage.0).sum(), measures as _);
@@ -269,14 +345,38 @@ impl Sampler {
     rs.cpu_power = zero_div(results.iter().map(|x| x.cpu_power).sum(), measures as _);
     rs.gpu_power = zero_div(results.iter().map(|x| x.gpu_power).sum(), measures as _);
     rs.ane_power = zero_div(results.iter().map(|x| x.ane_power).sum(), measures as _);
-    rs.all_power = rs.cpu_power + rs.gpu_power + rs.ane_power;
+    rs.other_power = zero_div(results.iter().map(|x| x.other_power).sum(), measures as _);
+    Ok(rs)
+  }
+
+  pub fn get_metrics(&mut self, duration: u64) -> WithError<Metrics> {
+    let mut rs = Metrics::default();
+
+    match self.ior.is_some() {
+      true => {
+        if let Ok(x) = self.get_ior_metrics(duration) {
+          rs.ecpu_usage = Some(x.ecpu_usage);
+          rs.pcpu_usage = Some(x.pcpu_usage);
+          rs.gpu_usage = Some(x.gpu_usage);
+          rs.cores = Some(x.cores).filter(|x| !x.is_empty());
+          rs.cpu_power = Some(x.cpu_power);
+          rs.gpu_power = Some(x.gpu_power);
+          rs.ane_power = Some(x.ane_power);
+          rs.all_power = Some(x.cpu_power + x.gpu_power + x.ane_power);
+          rs.other_power = Some(x.other_power);
+        }
+      }
+      // keep the update rate even without IOReport
+      false => std::thread::sleep(std::time::Duration::from_millis(duration)),
+    }
 
-    rs.memory = self.get_mem()?;
-    rs.temp = self.get_temp()?;
+    rs.memory = self.get_mem().ok();
+    rs.temp = self.get_temp().ok();
+    rs.fans = self.get_fans();
 
     rs.sys_power = match self.get_sys_power() {
-      Ok(val) => val.max(rs.all_power),
-      Err(_) => 0.0,
+      Ok(val) => Some(val.max(rs.all_power.unwrap_or(0.0))),
+      Err(_) => None,
     };
 
     Ok(rs)
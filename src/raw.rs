@@ -0,0 +1,237 @@
+use crate::history::now_ms;
+use crate::metrics::{Metrics, Sampler};
+use crate::sensors::{SensorsConfig, CPU, GPU};
+use crate::units::Units;
+
+type WithError<T> = Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum RawFormat {
+  /// Rust debug print of metrics, fixed units
+  #[default]
+  Debug,
+  /// One line per sample in display units
+  Text,
+  /// Header and one row per sample in display units
+  Csv,
+}
+
+// MARK: Text
+
+fn or_dash(value: Option<String>) -> String {
+  value.unwrap_or("-".to_string())
+}
+
+// e.g. "E 25% 1000 MHz · P 50% 2000 MHz · GPU 10%  500 MHz · CPU 1.50W ... · RAM 8.00/16.00GiB",
+// temperatures end with custom sensor groups of the sample, e.g. "CPU 45.0°C GPU 40.0°C SSD 38.0°C"
+fn text_line(m: &Metrics, units: &Units) -> String {
+  let freq = |x: Option<(u32, f32)>| {
+    x.map(|(freq, usage)| format!("{:.0}% {}", usage * 100.0, units.fmt_freq(freq as f64)))
+  };
+  let power = |x: Option<f32>| or_dash(x.map(|x| units.fmt_power(x as f64, 2)));
+  let size = |x: u64| units.size(x as f64);
+
+  let mut items = vec![
+    format!("E {}", or_dash(freq(m.ecpu_usage))),
+    format!("P {}", or_dash(freq(m.pcpu_usage))),
+    format!("GPU {}", or_dash(freq(m.gpu_usage))),
+    format!(
+      "CPU {} GPU {} ANE {} Other {} Total {}",
+      power(m.cpu_power),
+      power(m.gpu_power),
+      power(m.ane_power),
+      power(m.other_power),
+      power(m.sys_power)
+    ),
+  ];
+
+  if let Some(x) = &m.memory {
+    let label = units.size_label();
+    items.push(format!(
+      "RAM {:.2}/{:.2}{} Swap {:.2}/{:.2}{}",
+      size(x.ram_usage),
+      size(x.ram_total),
+      label,
+      size(x.swap_usage),
+      size(x.swap_total),
+      label
+    ));
+  }
+
+  if let Some(x) = &m.temp {
+    let temp = |x: Option<f32>| or_dash(x.map(|x| units.fmt_temp(x as f64, 1)));
+    let mut text = format!("CPU {} GPU {}", temp(x.cpu_temp_avg), temp(x.gpu_temp_avg));
+    for (name, group) in x.custom_groups() {
+      text.push_str(&format!(" {} {}", name.to_uppercase(), temp(Some(group.avg))));
+    }
+    items.push(text);
+  }
+
+  items.join(" · ")
+}
+
+// MARK: CSV
+
+// Custom sensor groups of the config, one CSV column each in this order
+fn csv_groups(sensors: &SensorsConfig) -> Vec<String> {
+  sensors.groups.keys().filter(|x| *x != CPU && *x != GPU).cloned().collect()
+}
+
+// Column names carry units, power is always in watts
+fn csv_header(units: &Units, groups: &[String]) -> String {
+  let freq = units.freq_label().0.to_lowercase();
+  let size = units.size_label().to_lowercase();
+  let temp = units.temp_label().trim_start_matches('°').to_lowercase();
+
+  let mut cols = vec!["ts".to_string()];
+  for x in ["ecpu", "pcpu", "gpu"] {
+    cols.push(format!("{}_usage_pct", x));
+    cols.push(format!("{}_freq_{}", x, freq));
+  }
+  for x in ["cpu", "gpu", "ane", "other", "sys"] {
+    cols.push(format!("{}_power_w", x));
+  }
+  for x in ["ram_usage", "ram_total", "swap_usage", "swap_total"] {
+    cols.push(format!("{}_{}", x, size));
+  }
+  for x in ["cpu", "gpu"] {
+    cols.push(format!("{}_temp_{}", x, temp));
+  }
+  for x in groups {
+    let name = x.to_lowercase().replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+    cols.push(format!("{}_temp_{}", name, temp));
+  }
+
+  cols.join(",")
+}
+
+// Empty cells for unavailable values, including groups without sensors in this sample
+fn csv_row(ts: u64, m: &Metrics, units: &Units, groups: &[String]) -> String {
+  let cell =
+    |x: Option<f64>, prec: usize| x.map_or(String::new(), |x| format!("{:.p$}", x, p = prec));
+  let freq_prec = units.freq_label().1;
+
+  let mut cols = vec![ts.to_string()];
+  for x in [m.ecpu_usage, m.pcpu_usage, m.gpu_usage] {
+    cols.push(cell(x.map(|x| x.1 as f64 * 100.0), 1));
+    cols.push(cell(x.map(|x| units.freq(x.0 as f64)), freq_prec));
+  }
+  for x in [m.cpu_power, m.gpu_power, m.ane_power, m.other_power, m.sys_power] {
+    cols.push(cell(x.map(|x| x as f64), 3));
+  }
+
+  let mem = m.memory.as_ref();
+  let sizes = [
+    mem.map(|x| x.ram_usage),
+    mem.map(|x| x.ram_total),
+    mem.map(|x| x.swap_usage),
+    mem.map(|x| x.swap_total),
+  ];
+  for x in sizes {
+    cols.push(cell(x.map(|x| units.size(x as f64)), 2));
+  }
+
+  let temp = m.temp.as_ref();
+  let custom = groups.iter().map(|name| temp.and_then(|x| x.groups.get(name)).map(|x| x.avg));
+  let temps = [temp.and_then(|x| x.cpu_temp_avg), temp.and_then(|x| x.gpu_temp_avg)];
+  for x in temps.into_iter().chain(custom) {
+    cols.push(cell(x.map(|x| units.temp(x as f64)), 1));
+  }
+
+  cols.join(",")
+}
+
+// MARK: Command
+
+pub fn print(
+  sampler: &mut Sampler,
+  msec: u64,
+  format: RawFormat,
+  units: Units,
+  sensors: &SensorsConfig,
+) -> WithError<()> {
+  let groups = csv_groups(sensors);
+  if format == RawFormat::Csv {
+    println!("{}", csv_header(&units, &groups));
+  }
+
+  loop {
+    let metrics = sampler.get_metrics(msec)?;
+    match format {
+      RawFormat::Debug => println!("{:?}", metrics),
+      RawFormat::Text => println!("{}", text_line(&metrics, &units)),
+      RawFormat::Csv => println!("{}", csv_row(now_ms(), &metrics, &units, &groups)),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::metrics::{MemMetrics, TempGroup, TempMetrics};
+  use crate::sensors::SensorGroup;
+  use crate::units::{FreqUnit, SizeUnit, TempUnit, GIB};
+  use std::collections::BTreeMap;
+
+  fn metrics() -> Metrics {
+    Metrics {
+      ecpu_usage: Some((1000, 0.25)),
+      cpu_power: Some(1.5),
+      memory: Some(MemMetrics {
+        ram_total: 16 * GIB as u64,
+        ram_usage: 8 * GIB as u64,
+        swap_total: 0,
+        swap_usage: 0,
+      }),
+      temp: Some(TempMetrics {
+        cpu_temp_avg: Some(45.0),
+        gpu_temp_avg: Some(40.0),
+        ..Default::default()
+      }),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_text_line() {
+    let line = text_line(&metrics(), &Units::default());
+    assert!(line.starts_with("E 25% 1000 MHz · P - · GPU - · CPU 1.50W GPU - "));
+    assert!(line.contains("RAM 8.00/16.00GiB Swap 0.00/0.00GiB · CPU 45.0°C GPU 40.0°C"));
+
+    let units = Units { temp: TempUnit::Fahrenheit, freq: FreqUnit::Ghz, ..Default::default() };
+    let line = text_line(&metrics(), &units);
+    assert!(line.starts_with("E 25% 1.00 GHz"));
+    assert!(line.ends_with("CPU 113.0°F GPU 104.0°F"));
+  }
+
+  #[test]
+  fn test_csv() {
+    let units = Units { size: SizeUnit::Gb, ..Default::default() };
+    let header = csv_header(&units, &[]);
+    let row = csv_row(1000, &metrics(), &units, &[]);
+    assert!(header.starts_with("ts,ecpu_usage_pct,ecpu_freq_mhz,pcpu_usage_pct"));
+    assert!(header.ends_with("swap_total_gb,cpu_temp_c,gpu_temp_c"));
+    assert_eq!(header.split(',').count(), row.split(',').count());
+    assert_eq!(row, "1000,25.0,1000,,,,,1.500,,,,,8.59,17.18,0.00,0.00,45.0,40.0");
+  }
+
+  #[test]
+  fn test_custom_groups() {
+    let mut m = metrics();
+    let ssd = TempGroup { avg: 38.0, ..Default::default() };
+    m.temp.as_mut().unwrap().groups = BTreeMap::from([("ssd".to_string(), ssd)]);
+
+    let line = text_line(&m, &Units::default());
+    assert!(line.ends_with("CPU 45.0°C GPU 40.0°C SSD 38.0°C"));
+
+    // columns follow the config, groups missing in the sample are empty
+    let names = [CPU, GPU, "ssd", "Battery 1"];
+    let groups = names.map(|x| (x.to_string(), SensorGroup::default()));
+    let sensors = SensorsConfig { groups: BTreeMap::from(groups), ..Default::default() };
+    let groups = csv_groups(&sensors);
+    let header = csv_header(&Units::default(), &groups);
+    let row = csv_row(1000, &m, &Units::default(), &groups);
+    assert!(header.ends_with("cpu_temp_c,gpu_temp_c,battery_1_temp_c,ssd_temp_c"));
+    assert!(row.ends_with(",45.0,40.0,,38.0"));
+  }
+}
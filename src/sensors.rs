@@ -0,0 +1,606 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::history::now_ms;
+use crate::metrics::TempGroup;
+use crate::sources::{IOHIDSensors, SMC};
+
+type WithError<T> = Result<T, Box<dyn std::error::Error>>;
+
+const FLOAT_TYPE: &str = "flt ";
+
+pub const CPU: &str = "cpu";
+pub const GPU: &str = "gpu";
+pub const OTHER: &str = "other"; // not in any group
+pub const IGNORED: &str = "ignored"; // not a temperature or not used on this machine
+
+// MARK: Config
+
+// Sensors matched by any pattern and by no `exclude` pattern, patterns support `*` and `?`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SensorGroup {
+  pub smc: Vec<String>, // SMC keys, e.g. "Tp*"
+  pub hid: Vec<String>, // IOHID product names, e.g. "NAND CH* temp"
+  pub exclude: Vec<String>,
+}
+
+// Validity of readings, rejected sensors are excluded from aggregates
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterConfig {
+  pub min: f32,        // °C
+  pub max: f32,        // °C
+  pub stuck_secs: u64, // same value for this many seconds, 0 (default) to disable
+  pub max_jump: f32,   // °C per second (changes within a second count as one second), 0 to disable
+}
+
+impl Default for FilterConfig {
+  fn default() -> Self {
+    Self { min: 5.0, max: 125.0, stuck_secs: 0, max_jump: 25.0 }
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SensorsConfig {
+  pub groups: BTreeMap<String, SensorGroup>, // replace default "cpu" / "gpu" groups or add new ones
+  pub offsets: BTreeMap<String, f32>,        // °C added to values of sensors by name
+  pub filter: FilterConfig,
+}
+
+fn patterns(items: &[&str]) -> Vec<String> {
+  items.iter().map(|x| x.to_string()).collect()
+}
+
+// Unfortunately, it is not known which keys are responsible for what.
+// Basically in the code that can be found publicly "Tp" is used for CPU and "Tg" for GPU.
+fn default_groups() -> BTreeMap<String, SensorGroup> {
+  let cpu = SensorGroup {
+    smc: patterns(&["Tp*"]),
+    hid: patterns(&["pACC MTR Temp Sensor*", "eACC MTR Temp Sensor*"]),
+    exclude: vec![],
+  };
+  let gpu = SensorGroup {
+    smc: patterns(&["Tg*"]),
+    hid: patterns(&["GPU MTR Temp Sensor*"]),
+    exclude: vec![],
+  };
+  BTreeMap::from([(CPU.to_string(), cpu), (GPU.to_string(), gpu)])
+}
+
+// Glob match with `*` (any characters) and `?` (one character)
+pub fn glob(pattern: &str, name: &str) -> bool {
+  let (p, n) = (pattern.chars().collect::<Vec<_>>(), name.chars().collect::<Vec<_>>());
+  let (mut pi, mut ni) = (0, 0);
+  let mut star: Option<(usize, usize)> = None; // positions after last `*`
+
+  while ni < n.len() {
+    match p.get(pi) {
+      Some('*') => {
+        star = Some((pi + 1, ni));
+        pi += 1;
+      }
+      Some(x) if *x == '?' || *x == n[ni] => {
+        pi += 1;
+        ni += 1;
+      }
+      _ => match star {
+        Some((sp, sn)) => {
+          (pi, ni) = (sp, sn + 1);
+          star = Some((sp, sn + 1));
+        }
+        None => return false,
+      },
+    }
+  }
+
+  p[pi..].iter().all(|x| *x == '*')
+}
+
+// MARK: Classification
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+  Hid,
+  Smc,
+}
+
+impl Source {
+  fn label(&self) -> &'static str {
+    match self {
+      Source::Hid => "hid",
+      Source::Smc => "smc",
+    }
+  }
+}
+
+pub fn is_float(data_type: &str, size: usize) -> bool {
+  data_type == FLOAT_TYPE && size == 4
+}
+
+// Default groups with configured ones applied, checked in name order
+#[derive(Debug, Clone, Default)]
+pub struct Mapping {
+  groups: Vec<(String, SensorGroup, bool)>, // name, patterns, from config
+  offsets: BTreeMap<String, f32>,
+}
+
+impl Mapping {
+  pub fn new(cfg: &SensorsConfig) -> Self {
+    let mut groups =
+      default_groups().into_iter().map(|(k, v)| (k, (v, false))).collect::<BTreeMap<_, _>>();
+    groups.extend(cfg.groups.iter().map(|(k, v)| (k.clone(), (v.clone(), true))));
+
+    let groups = groups.into_iter().map(|(k, (v, custom))| (k, v, custom)).collect();
+    Self { groups, offsets: cfg.offsets.clone() }
+  }
+
+  // Group of the sensor with the pattern that chose it
+  pub fn classify(&self, source: Source, name: &str) -> (String, String) {
+    for (group, x, custom) in &self.groups {
+      if x.exclude.iter().any(|p| glob(p, name)) {
+        continue;
+      }
+
+      let items = if source == Source::Smc { &x.smc } else { &x.hid };
+      if let Some(p) = items.iter().find(|p| glob(p, name)) {
+        let origin = if *custom { "config" } else { "default" };
+        return (group.clone(), format!("{} pattern \"{}\" ({})", source.label(), p, origin));
+      }
+    }
+
+    (OTHER.to_string(), "no group pattern".to_string())
+  }
+
+  pub fn offset(&self, name: &str) -> f32 {
+    self.offsets.get(name).copied().unwrap_or(0.0)
+  }
+
+  // Groups reading IOHID sensors besides CPU / GPU
+  pub fn custom_hid(&self) -> bool {
+    self.groups.iter().any(|(name, x, _)| name != CPU && name != GPU && !x.hid.is_empty())
+  }
+}
+
+// MARK: Sensors
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Sensor {
+  pub source: Source,
+  pub name: String,       // SMC key or HID product name
+  pub value: Option<f32>, // Celsius, with offset applied
+  #[serde(skip_serializing_if = "is_zero")]
+  pub offset: f32,
+  pub category: String, // group name, "other" or "ignored"
+  pub reason: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub rejected: Option<String>, // by validity filter
+}
+
+fn is_zero(x: &f32) -> bool {
+  *x == 0.0
+}
+
+impl Sensor {
+  pub fn smc(key: &str, data_type: &str, data: &[u8], mapping: &Mapping) -> Self {
+    if !is_float(data_type, data.len()) {
+      let reason = format!("type \"{}\" ({} bytes) is not flt", data_type, data.len());
+      let category = IGNORED.to_string();
+      return Self {
+        source: Source::Smc,
+        name: key.to_string(),
+        value: None,
+        offset: 0.0,
+        category,
+        reason,
+        rejected: None,
+      };
+    }
+
+    let value = f32::from_le_bytes(data.try_into().unwrap_or_default());
+    Self::new(Source::Smc, key, value, mapping)
+  }
+
+  pub fn hid(name: &str, value: f32, mapping: &Mapping) -> Self {
+    Self::new(Source::Hid, name, value, mapping)
+  }
+
+  fn new(source: Source, name: &str, value: f32, mapping: &Mapping) -> Self {
+    let (category, reason) = mapping.classify(source, name);
+    let offset = mapping.offset(name);
+    let value = Some(value + offset);
+    Self { source, name: name.to_string(), value, offset, category, reason, rejected: None }
+  }
+}
+
+// CPU / GPU temperatures are read from SMC when it has CPU sensors, from IOHID otherwise;
+// CPU / GPU sensors of the unused source are marked as ignored, custom groups use both.
+pub fn mark_unused(items: &mut [Sensor]) {
+  let smc_used = items.iter().any(|x| x.source == Source::Smc && x.category == CPU);
+  let unused = if smc_used { Source::Hid } else { Source::Smc };
+  let reason = match smc_used {
+    true => "SMC sensors are used on this machine",
+    false => "IOHID sensors are used on this machine",
+  };
+
+  let items = items.iter_mut().filter(|x| x.source == unused);
+  for x in items.filter(|x| x.category == CPU || x.category == GPU) {
+    x.reason = format!("{}; would be {} by {}", reason, x.category, x.reason);
+    x.category = IGNORED.to_string();
+  }
+}
+
+// MARK: Filter
+
+#[derive(Debug, Clone, Copy)]
+struct Track {
+  value: f32,
+  since: u64,                   // time of the first sample with this value, msec
+  accepted: Option<(f32, u64)>, // last value that passed all checks and its time, msec
+}
+
+// Sanity checks of sensor values over time: range, stuck value and jump rate
+#[derive(Debug, Clone, Default)]
+pub struct SensorFilter {
+  cfg: FilterConfig,
+  tracks: HashMap<String, Track>,
+}
+
+impl SensorFilter {
+  pub fn new(cfg: FilterConfig) -> Self {
+    Self { cfg, tracks: HashMap::new() }
+  }
+
+  // Reason to reject the value, jumps are measured from the last accepted value,
+  // so a spike is rejected but the return to normal is not
+  pub fn check(&mut self, name: &str, value: f32, ts: u64) -> Option<String> {
+    let prev = self.tracks.get(name).copied();
+    let since = match prev {
+      Some(x) if x.value == value => x.since,
+      _ => ts,
+    };
+
+    let accepted = prev.and_then(|x| x.accepted);
+    let rejected = self.reject(value, ts, since, accepted);
+    let accepted = if rejected.is_none() { Some((value, ts)) } else { accepted };
+    self.tracks.insert(name.to_string(), Track { value, since, accepted });
+    rejected
+  }
+
+  fn reject(
+    &self,
+    value: f32,
+    ts: u64,
+    since: u64,
+    accepted: Option<(f32, u64)>,
+  ) -> Option<String> {
+    let cfg = self.cfg;
+    if !(cfg.min..=cfg.max).contains(&value) {
+      return Some(format!("out of range {}..{}°C", cfg.min, cfg.max));
+    }
+
+    let stuck = ts.saturating_sub(since) / 1000;
+    if cfg.stuck_secs > 0 && stuck >= cfg.stuck_secs {
+      return Some(format!("stuck for {}s", stuck));
+    }
+
+    if let Some((last, last_ts)) = accepted.filter(|x| cfg.max_jump > 0.0 && ts > x.1) {
+      let secs = (ts - last_ts) as f32 / 1000.0;
+      let rate = (value - last).abs() / secs.max(1.0);
+      if rate > cfg.max_jump {
+        return Some(format!("jumped {:.1}°C in {:.1}s", value - last, secs));
+      }
+    }
+
+    None
+  }
+
+  pub fn apply(&mut self, items: &mut [Sensor], ts: u64) {
+    for x in items.iter_mut() {
+      if let Some(value) = x.value {
+        x.rejected = self.check(&x.name, value, ts);
+      }
+    }
+  }
+}
+
+// Min / avg / max temperature per group, ignored, other and rejected sensors are skipped
+pub fn group_stats(items: &[Sensor]) -> BTreeMap<String, TempGroup> {
+  let mut groups: BTreeMap<String, Vec<(&str, f32)>> = BTreeMap::new();
+  let items = items.iter().filter(|x| x.rejected.is_none());
+  for x in items.filter(|x| x.category != IGNORED && x.category != OTHER) {
+    if let Some(value) = x.value {
+      groups.entry(x.category.clone()).or_default().push((&x.name, value));
+    }
+  }
+
+  let stats = |values: Vec<(&str, f32)>| {
+    let hot = values.iter().fold(values[0], |acc, x| if x.1 > acc.1 { *x } else { acc });
+    let min = values.iter().map(|x| x.1).fold(f32::INFINITY, f32::min);
+    let avg = values.iter().map(|x| x.1).sum::<f32>() / values.len() as f32;
+    TempGroup { min, avg, max: hot.1, hotspot: hot.0.to_string(), count: values.len() }
+  };
+
+  groups.into_iter().map(|(k, v)| (k, stats(v))).collect()
+}
+
+// All SMC "T*" keys and IOHID temperature sensors, unavailable sources are skipped
+fn read_all(smc: &mut Option<(SMC, Vec<String>)>, mapping: &Mapping) -> Vec<Sensor> {
+  let mut items = Vec::new();
+
+  if let Some((smc, keys)) = smc.as_mut() {
+    for key in keys.iter() {
+      if let Ok(val) = smc.read_val(key) {
+        items.push(Sensor::smc(key, &val.unit, &val.data, mapping));
+      }
+    }
+  }
+
+  if let Ok(hid) = IOHIDSensors::new() {
+    for (name, value) in hid.get_metrics() {
+      items.push(Sensor::hid(&name, value, mapping));
+    }
+  }
+
+  mark_unused(&mut items);
+  items
+}
+
+// Last of `samples` readings, taken `interval` msec apart for stuck / jump checks
+pub fn collect(cfg: &SensorsConfig, samples: usize, interval: u64) -> Vec<Sensor> {
+  let mapping = Mapping::new(cfg);
+  let mut filter = SensorFilter::new(cfg.filter);
+
+  let mut smc = SMC::new().ok().map(|mut smc| {
+    let keys = smc.read_all_keys().unwrap_or_default();
+    (smc, keys.into_iter().filter(|x| x.starts_with('T')).collect())
+  });
+
+  let mut items = Vec::new();
+  for i in 0..samples.max(1) {
+    if i > 0 {
+      std::thread::sleep(Duration::from_millis(interval));
+    }
+
+    items = read_all(&mut smc, &mapping);
+    filter.apply(&mut items, now_ms());
+  }
+
+  items
+}
+
+// MARK: Command
+
+fn value_text(value: Option<f32>) -> String {
+  match value {
+    Some(x) => format!("{:.2}", x),
+    None => "-".to_string(),
+  }
+}
+
+fn print_groups(items: &[Sensor], json: bool) -> WithError<()> {
+  let groups = group_stats(items);
+  if json {
+    println!("{}", serde_json::to_string_pretty(&groups)?);
+    return Ok(());
+  }
+
+  let width = groups.keys().map(|x| x.len()).max().unwrap_or(0).max(5);
+  println!(
+    "{:<w$}  {:>5}  {:>7}  {:>7}  {:>7}  HOTSPOT",
+    "GROUP",
+    "COUNT",
+    "MIN",
+    "AVG",
+    "MAX",
+    w = width
+  );
+  for (name, x) in &groups {
+    println!(
+      "{:<w$}  {:>5}  {:>7.2}  {:>7.2}  {:>7.2}  {}",
+      name,
+      x.count,
+      x.min,
+      x.avg,
+      x.max,
+      x.hotspot,
+      w = width
+    );
+  }
+
+  Ok(())
+}
+
+// Every sensor, or only aggregates per group
+pub fn list(
+  cfg: &SensorsConfig,
+  samples: usize,
+  interval: u64,
+  groups: bool,
+  json: bool,
+) -> WithError<()> {
+  let items = collect(cfg, samples, interval);
+  if groups {
+    return print_groups(&items, json);
+  }
+
+  if json {
+    println!("{}", serde_json::to_string_pretty(&items)?);
+    return Ok(());
+  }
+
+  let width = items.iter().map(|x| x.name.len()).max().unwrap_or(0).max(4);
+  println!("{:<6}  {:<w$}  {:>7}  {:<8}  REASON", "SOURCE", "NAME", "VALUE", "CATEGORY", w = width);
+  for x in &items {
+    let source = if x.source == Source::Smc { "SMC" } else { "HID" };
+    let (value, category) = (value_text(x.value), &x.category);
+    let reason = match &x.rejected {
+      Some(why) => format!("{}; rejected: {}", x.reason, why),
+      None => x.reason.clone(),
+    };
+    println!("{:<6}  {:<w$}  {:>7}  {:<8}  {}", source, x.name, value, category, reason, w = width);
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn smc(key: &str, value: f32, mapping: &Mapping) -> Sensor {
+    Sensor::smc(key, "flt ", &value.to_le_bytes(), mapping)
+  }
+
+  #[test]
+  fn test_glob() {
+    assert!(glob("Tp*", "Tp01") && glob("T?0T", "TB0T") && glob("*", ""));
+    assert!(glob("NAND CH* temp", "NAND CH0 temp") && glob("*a*b", "xaab"));
+    assert!(!glob("Tp*", "Tg01") && !glob("T?0T", "TB0") && !glob("Tp01", "Tp011"));
+  }
+
+  #[test]
+  fn test_default_mapping() {
+    let mapping = Mapping::new(&SensorsConfig::default());
+    let (group, reason) = mapping.classify(Source::Smc, "Tp01");
+    assert_eq!((group.as_str(), reason.as_str()), (CPU, "smc pattern \"Tp*\" (default)"));
+    assert_eq!(mapping.classify(Source::Smc, "Tg0f").0, GPU);
+    assert_eq!(mapping.classify(Source::Smc, "TB0T").0, OTHER);
+    assert_eq!(mapping.classify(Source::Hid, "pACC MTR Temp Sensor3").0, CPU);
+    assert_eq!(mapping.classify(Source::Hid, "NAND CH0 temp").0, OTHER);
+
+    let item = Sensor::smc("TC0P", "sp78", &[0x2d, 0x80], &mapping);
+    assert_eq!((item.category.as_str(), item.value), (IGNORED, None));
+  }
+
+  #[test]
+  fn test_custom_mapping() {
+    let text = r#"{
+      "groups": {
+        "cpu": { "smc": ["Tp*", "Te*"], "exclude": ["Tp0A"] },
+        "ssd": { "hid": ["NAND*"] },
+        "battery": { "smc": ["TB?T"] }
+      },
+      "offsets": { "Tp01": -2.5 }
+    }"#;
+    let mapping = Mapping::new(&serde_json::from_str(text).unwrap());
+
+    assert_eq!(mapping.classify(Source::Smc, "Te05").0, CPU);
+    assert_eq!(mapping.classify(Source::Smc, "Tp0A").0, OTHER);
+    assert_eq!(mapping.classify(Source::Smc, "TB1T").0, "battery");
+    assert_eq!(mapping.classify(Source::Hid, "NAND CH0 temp").0, "ssd");
+    // "cpu" group replaced, default "gpu" kept
+    assert_eq!(mapping.classify(Source::Hid, "pACC MTR Temp Sensor3").0, OTHER);
+    assert_eq!(mapping.classify(Source::Smc, "Tg0f").0, GPU);
+    assert!(mapping.custom_hid());
+
+    let item = smc("Tp01", 50.0, &mapping);
+    assert_eq!((item.value, item.offset), (Some(47.5), -2.5));
+  }
+
+  fn series(filter: &mut SensorFilter, values: &[f32]) -> Vec<Option<String>> {
+    let items = values.iter().enumerate();
+    items.map(|(i, x)| filter.check("Tp01", *x, i as u64 * 1000)).collect()
+  }
+
+  #[test]
+  fn test_filter_range() {
+    let mut filter = SensorFilter::new(FilterConfig::default());
+    let rs = series(&mut filter, &[0.0, 45.0, 128.0, f32::NAN, -1.0]);
+    assert_eq!(rs[0].as_deref(), Some("out of range 5..125°C"));
+    assert_eq!(rs[1], None);
+    assert!(rs[2].is_some() && rs[3].is_some() && rs[4].is_some());
+  }
+
+  #[test]
+  fn test_filter_stuck() {
+    let cfg = FilterConfig { stuck_secs: 2, ..Default::default() };
+    let mut filter = SensorFilter::new(cfg);
+    let rs = series(&mut filter, &[40.0, 40.0, 40.0, 40.0, 40.5, 40.0]);
+    assert_eq!(
+      rs.iter().map(|x| x.is_some()).collect::<Vec<_>>(),
+      [false, false, true, true, false, false]
+    );
+    assert_eq!(rs[3].as_deref(), Some("stuck for 3s"));
+
+    // time based, does not depend on the number of samples
+    assert_eq!(filter.check("Tg01", 40.0, 0), None);
+    assert_eq!(filter.check("Tg01", 40.0, 1900), None);
+    assert_eq!(filter.check("Tg01", 40.0, 2000).as_deref(), Some("stuck for 2s"));
+
+    // disabled by default, steady readings of an idle sensor stay accepted
+    let mut filter = SensorFilter::new(FilterConfig::default());
+    assert!(series(&mut filter, &[40.0; 100]).iter().all(|x| x.is_none()));
+  }
+
+  #[test]
+  fn test_filter_jump() {
+    let mut filter = SensorFilter::new(FilterConfig::default());
+    // spike rejected, the return to normal is measured from the last accepted value
+    let rs = series(&mut filter, &[45.0, 50.0, 90.0, 50.0, 52.0]);
+    assert_eq!(
+      rs.iter().map(|x| x.is_some()).collect::<Vec<_>>(),
+      [false, false, true, false, false]
+    );
+    assert_eq!(rs[2].as_deref(), Some("jumped 40.0°C in 1.0s"));
+
+    // rate over longer interval, short intervals count as one second
+    assert_eq!(filter.check("Tg01", 40.0, 0), None);
+    assert_eq!(filter.check("Tg01", 80.0, 5000), None);
+    assert!(filter.check("Tg01", 50.0, 5100).is_some());
+    assert_eq!(filter.check("Tg01", 60.0, 5200), None);
+  }
+
+  #[test]
+  fn test_rejected_not_aggregated() {
+    let mapping = Mapping::new(&SensorsConfig::default());
+    let mut items = vec![smc("Tp01", 45.0, &mapping), smc("Tp05", 0.0, &mapping)];
+
+    let mut filter = SensorFilter::new(FilterConfig::default());
+    filter.apply(&mut items, 0);
+    assert!(items[1].rejected.is_some());
+
+    let groups = group_stats(&items);
+    assert_eq!((groups[CPU].min, groups[CPU].max, groups[CPU].count), (45.0, 45.0, 1));
+  }
+
+  #[test]
+  fn test_mark_unused() {
+    let mapping = Mapping::new(&SensorsConfig {
+      groups: BTreeMap::from([(
+        "ssd".to_string(),
+        SensorGroup { hid: patterns(&["NAND*"]), ..Default::default() },
+      )]),
+      ..Default::default()
+    });
+
+    let mut items = vec![
+      smc("Tp01", 45.0, &mapping),
+      smc("Tp05", 55.0, &mapping),
+      Sensor::hid("pACC MTR Temp Sensor0", 44.0, &mapping),
+      Sensor::hid("NAND CH0 temp", 30.0, &mapping),
+    ];
+
+    mark_unused(&mut items);
+    assert_eq!(items[0].category, CPU);
+    assert_eq!(items[2].category, IGNORED);
+    assert!(items[2].reason.starts_with("SMC sensors are used on this machine; would be cpu by"));
+    assert_eq!(items[3].category, "ssd");
+
+    let groups = group_stats(&items);
+    assert_eq!(groups.keys().collect::<Vec<_>>(), vec![CPU, "ssd"]);
+    assert_eq!((groups[CPU].min, groups[CPU].avg, groups[CPU].max), (45.0, 50.0, 55.0));
+    assert_eq!((groups[CPU].hotspot.as_str(), groups[CPU].count), ("Tp05", 2));
+    assert_eq!(groups["ssd"].hotspot, "NAND CH0 temp");
+
+    // no SMC CPU keys: IOHID is used
+    let mut items =
+      vec![smc("Tg01", 30.0, &mapping), Sensor::hid("GPU MTR Temp Sensor1", 40.0, &mapping)];
+    mark_unused(&mut items);
+    assert_eq!((items[0].category.as_str(), items[1].category.as_str()), (IGNORED, GPU));
+  }
+}
@@ -0,0 +1,233 @@
+use std::fmt;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::history::now_ms;
+use crate::sources::SMC;
+
+type WithError<T> = Result<T, Box<dyn std::error::Error>>;
+
+// MARK: Values
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum SmcValue {
+  Float(f32),
+  Int(i64),
+  UInt(u64),
+  Bool(bool),
+  Text(String),
+}
+
+impl fmt::Display for SmcValue {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SmcValue::Float(x) => write!(f, "{}", x),
+      SmcValue::Int(x) => write!(f, "{}", x),
+      SmcValue::UInt(x) => write!(f, "{}", x),
+      SmcValue::Bool(x) => write!(f, "{}", x),
+      SmcValue::Text(x) => write!(f, "\"{}\"", x),
+    }
+  }
+}
+
+impl SmcValue {
+  pub fn as_f64(&self) -> Option<f64> {
+    match self {
+      SmcValue::Float(x) => Some(*x as f64),
+      SmcValue::Int(x) => Some(*x as f64),
+      SmcValue::UInt(x) => Some(*x as f64),
+      _ => None,
+    }
+  }
+}
+
+fn hex(data: &[u8]) -> String {
+  data.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+// Decodes raw bytes by SMC type (FourCC), None for unknown types and unexpected sizes.
+// Floats are little-endian on Apple Silicon, integers and fixed-point – big-endian.
+pub fn decode(data_type: &str, data: &[u8]) -> Option<SmcValue> {
+  let be = |n: usize| match data.len() == n {
+    true => Some(data.iter().fold(0u64, |acc, x| (acc << 8) + *x as u64)),
+    false => None,
+  };
+
+  let value = match data_type {
+    "flt " => SmcValue::Float(f32::from_le_bytes(data.try_into().ok()?)),
+    "ioft" => SmcValue::Float((u64::from_le_bytes(data.try_into().ok()?) as f64 / 65536.0) as f32),
+    "flag" => SmcValue::Bool(*data.first()? != 0),
+    "ui8 " => SmcValue::UInt(be(1)?),
+    "ui16" => SmcValue::UInt(be(2)?),
+    "ui32" => SmcValue::UInt(be(4)?),
+    "ui64" => SmcValue::UInt(be(8)?),
+    "si8 " => SmcValue::Int(be(1)? as u8 as i8 as i64),
+    "si16" => SmcValue::Int(be(2)? as u16 as i16 as i64),
+    "si32" => SmcValue::Int(be(4)? as u32 as i32 as i64),
+    "si64" => SmcValue::Int(be(8)? as i64),
+    x if x.starts_with("ch8") => {
+      let text = String::from_utf8_lossy(data);
+      SmcValue::Text(text.trim_end_matches('\0').to_string())
+    }
+    // 16-bit fixed-point, last hex digit is the number of fraction bits: fpe2, sp78
+    x if x.len() == 4 && (x.starts_with("fp") || x.starts_with("sp")) => {
+      let frac = u32::from_str_radix(&x[3..], 16).ok()?;
+      let raw = be(2)?;
+      let raw = if x.starts_with("sp") { raw as u16 as i16 as f32 } else { raw as f32 };
+      SmcValue::Float(raw / (1u32 << frac) as f32)
+    }
+    _ => return None,
+  };
+
+  Some(value)
+}
+
+// MARK: Keys
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyEntry {
+  pub key: String,
+  #[serde(rename = "type")]
+  pub data_type: String,
+  pub size: usize,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub value: Option<SmcValue>, // None if type is not known
+  pub raw: String, // hex
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+impl KeyEntry {
+  fn new(key: &str, data_type: &str, data: &[u8]) -> Self {
+    Self {
+      key: key.to_string(),
+      data_type: data_type.to_string(),
+      size: data.len(),
+      value: decode(data_type, data),
+      raw: hex(data),
+      error: None,
+    }
+  }
+
+  fn failed(key: &str, error: String) -> Self {
+    let (data_type, raw) = (String::new(), String::new());
+    Self { key: key.to_string(), data_type, size: 0, value: None, raw, error: Some(error) }
+  }
+
+  // Decoded value or raw bytes
+  fn value_text(&self) -> String {
+    match (&self.value, &self.error) {
+      (_, Some(err)) => format!("error: {}", err),
+      (Some(x), _) => x.to_string(),
+      (None, _) => format!("0x{}", self.raw),
+    }
+  }
+}
+
+fn read_key(smc: &mut SMC, key: &str) -> KeyEntry {
+  match smc.read_val(key) {
+    Ok(val) => KeyEntry::new(key, &val.unit, &val.data),
+    Err(err) => KeyEntry::failed(key, err.to_string()),
+  }
+}
+
+fn print_table(items: &[KeyEntry]) {
+  println!("{:<4}  {:<4}  {:>4}  VALUE", "KEY", "TYPE", "SIZE");
+  for x in items {
+    println!("{:<4}  {:<4}  {:>4}  {}", x.key, x.data_type, x.size, x.value_text());
+  }
+}
+
+fn print_items(items: &[KeyEntry], json: bool) -> WithError<()> {
+  match json {
+    true => println!("{}", serde_json::to_string_pretty(items)?),
+    false => print_table(items),
+  }
+  Ok(())
+}
+
+// MARK: Commands
+
+// `type` matches FourCC without trailing spaces, e.g. "flt" or "ui8"
+pub fn list(prefix: Option<&str>, data_type: Option<&str>, json: bool) -> WithError<()> {
+  let mut smc = SMC::new()?;
+  let keys = smc.read_all_keys()?;
+
+  let items = keys
+    .iter()
+    .filter(|x| prefix.is_none_or(|p| x.starts_with(p)))
+    .map(|x| read_key(&mut smc, x))
+    .filter(|x| data_type.is_none_or(|t| x.data_type.trim_end() == t.trim_end()))
+    .collect::<Vec<_>>();
+
+  print_items(&items, json)
+}
+
+pub fn read(keys: &[String], json: bool) -> WithError<()> {
+  let mut smc = SMC::new()?;
+  let items = keys.iter().map(|x| read_key(&mut smc, x)).collect::<Vec<_>>();
+  print_items(&items, json)
+}
+
+// One line per sample: "KEY=value ..." or JSON object with timestamp, msec
+pub fn watch(keys: &[String], interval: u64, json: bool) -> WithError<()> {
+  let mut smc = SMC::new()?;
+
+  loop {
+    let items = keys.iter().map(|x| read_key(&mut smc, x)).collect::<Vec<_>>();
+    match json {
+      true => {
+        let values = items.iter().map(|x| (x.key.clone(), serde_json::json!(x.value)));
+        let values = values.collect::<serde_json::Map<_, _>>();
+        println!("{}", serde_json::json!({ "ts": now_ms(), "values": values }));
+      }
+      false => {
+        let values = items.iter().map(|x| format!("{}={}", x.key, x.value_text()));
+        println!("{}", values.collect::<Vec<_>>().join("  "));
+      }
+    }
+
+    std::thread::sleep(Duration::from_millis(interval.max(100)));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_decode_numbers() {
+    let data = 45.25f32.to_le_bytes();
+    assert_eq!(decode("flt ", &data), Some(SmcValue::Float(45.25)));
+    assert_eq!(decode("ui32", &[0, 0, 0x04, 0xd2]), Some(SmcValue::UInt(1234)));
+    assert_eq!(decode("si16", &[0xff, 0xfe]), Some(SmcValue::Int(-2)));
+    assert_eq!(decode("flag", &[1]), Some(SmcValue::Bool(true)));
+    assert_eq!(decode("ioft", &(3 << 16 | 0x8000u64).to_le_bytes()), Some(SmcValue::Float(3.5)));
+
+    // wrong size or unknown type
+    assert_eq!(decode("ui16", &[1]), None);
+    assert_eq!(decode("{jst", &[1, 2]), None);
+  }
+
+  #[test]
+  fn test_decode_fixed_point() {
+    assert_eq!(decode("fpe2", &[0x00, 0x0a]), Some(SmcValue::Float(2.5)));
+    assert_eq!(decode("sp78", &[0x2d, 0x80]), Some(SmcValue::Float(45.5)));
+    assert_eq!(decode("sp78", &[0xff, 0x00]), Some(SmcValue::Float(-1.0)));
+  }
+
+  #[test]
+  fn test_key_entry() {
+    let item = KeyEntry::new("RPlt", "ch8*", b"J314s\0\0\0");
+    assert_eq!(item.value_text(), "\"J314s\"");
+
+    let item = KeyEntry::new("XXXX", "{rev", &[1, 2, 0xab]);
+    assert_eq!((item.size, item.value_text()), (3, "0x0102ab".to_string()));
+
+    let json =
+      serde_json::to_string(&KeyEntry::new("TC0P", "flt ", &45.5f32.to_le_bytes())).unwrap();
+    assert_eq!(json, r#"{"key":"TC0P","type":"flt ","size":4,"value":45.5,"raw":"00003642"}"#);
+  }
+}
@@ -0,0 +1,332 @@
+use std::fmt::Write;
+
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::style::{Color, Modifier};
+use serde::{Deserialize, Serialize};
+
+// SVG cell size, px
+const CELL_W: f64 = 8.4;
+const CELL_H: f64 = 17.0;
+
+// default colors of the rendered terminal
+const DEFAULT_FG: &str = "#d4d4d4";
+const DEFAULT_BG: &str = "#1e1e1e";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+  /// Plain text
+  #[default]
+  Text,
+  /// Text with ANSI color escapes
+  Ansi,
+  Svg,
+  Html,
+}
+
+impl Format {
+  pub fn extension(&self) -> &'static str {
+    match self {
+      Format::Text => "txt",
+      Format::Ansi => "ans",
+      Format::Svg => "svg",
+      Format::Html => "html",
+    }
+  }
+}
+
+// MARK: Helpers
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Style {
+  fg: Color,
+  bg: Color,
+  mods: Modifier,
+}
+
+impl Style {
+  fn of(cell: &Cell) -> Self {
+    Self { fg: cell.fg, bg: cell.bg, mods: cell.modifier }
+  }
+
+  // (fg, bg) as css colors with reversed modifier applied
+  fn css_colors(&self) -> (String, String) {
+    let fg = css_color(self.fg).unwrap_or(DEFAULT_FG.to_string());
+    let bg = css_color(self.bg).unwrap_or(DEFAULT_BG.to_string());
+    match self.mods.contains(Modifier::REVERSED) {
+      true => (bg, fg),
+      false => (fg, bg),
+    }
+  }
+}
+
+// Row of the buffer split into runs of cells with the same style
+fn runs(buf: &Buffer, y: u16) -> Vec<(u16, Style, String)> {
+  let mut items: Vec<(u16, Style, String)> = Vec::new();
+
+  for x in buf.area.left()..buf.area.right() {
+    let cell = &buf[(x, y)];
+    if cell.skip {
+      continue;
+    }
+
+    let style = Style::of(cell);
+    match items.last_mut() {
+      Some(last) if last.1 == style => last.2.push_str(cell.symbol()),
+      _ => items.push((x, style, cell.symbol().to_string())),
+    }
+  }
+
+  items
+}
+
+const XTERM_16: [&str; 16] = [
+  "#000000", "#cd0000", "#00cd00", "#cdcd00", "#0000ee", "#cd00cd", "#00cdcd", "#e5e5e5",
+  "#7f7f7f", "#ff0000", "#00ff00", "#ffff00", "#5c5cff", "#ff00ff", "#00ffff", "#ffffff",
+];
+
+fn ansi_index(color: Color) -> Option<u8> {
+  let idx = match color {
+    Color::Black => 0,
+    Color::Red => 1,
+    Color::Green => 2,
+    Color::Yellow => 3,
+    Color::Blue => 4,
+    Color::Magenta => 5,
+    Color::Cyan => 6,
+    Color::Gray => 7,
+    Color::DarkGray => 8,
+    Color::LightRed => 9,
+    Color::LightGreen => 10,
+    Color::LightYellow => 11,
+    Color::LightBlue => 12,
+    Color::LightMagenta => 13,
+    Color::LightCyan => 14,
+    Color::White => 15,
+    Color::Indexed(x) => x,
+    _ => return None,
+  };
+
+  Some(idx)
+}
+
+// None for terminal default color
+fn css_color(color: Color) -> Option<String> {
+  if let Color::Rgb(r, g, b) = color {
+    return Some(format!("#{:02x}{:02x}{:02x}", r, g, b));
+  }
+
+  let idx = ansi_index(color)? as usize;
+  let hex = match idx {
+    0..=15 => XTERM_16[idx].to_string(),
+    16..=231 => {
+      let level = |x: usize| if x == 0 { 0 } else { 55 + x * 40 };
+      let (r, g, b) = ((idx - 16) / 36, (idx - 16) / 6 % 6, (idx - 16) % 6);
+      format!("#{:02x}{:02x}{:02x}", level(r), level(g), level(b))
+    }
+    _ => {
+      let x = 8 + (idx - 232) * 10;
+      format!("#{:02x}{:02x}{:02x}", x, x, x)
+    }
+  };
+
+  Some(hex)
+}
+
+fn escape(text: &str) -> String {
+  text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// MARK: Export
+
+fn to_text(buf: &Buffer) -> String {
+  let mut rs = String::new();
+  for y in buf.area.top()..buf.area.bottom() {
+    let line = runs(buf, y).into_iter().map(|x| x.2).collect::<String>();
+    rs.push_str(line.trim_end());
+    rs.push('\n');
+  }
+  rs
+}
+
+fn sgr(style: &Style) -> String {
+  let mut codes = vec!["0".to_string()];
+
+  let mods = [
+    (Modifier::BOLD, "1"),
+    (Modifier::DIM, "2"),
+    (Modifier::ITALIC, "3"),
+    (Modifier::UNDERLINED, "4"),
+    (Modifier::REVERSED, "7"),
+  ];
+  for (m, code) in mods {
+    if style.mods.contains(m) {
+      codes.push(code.to_string());
+    }
+  }
+
+  let color = |color: Color, base: u8| match (color, ansi_index(color)) {
+    (Color::Rgb(r, g, b), _) => Some(format!("{};2;{};{};{}", base + 8, r, g, b)),
+    (_, Some(x)) if x < 8 => Some(format!("{}", base + x)),
+    (_, Some(x)) if x < 16 => Some(format!("{}", base + 60 + x - 8)),
+    (_, Some(x)) => Some(format!("{};5;{}", base + 8, x)),
+    _ => None,
+  };
+
+  codes.extend(color(style.fg, 30));
+  codes.extend(color(style.bg, 40));
+  format!("\x1b[{}m", codes.join(";"))
+}
+
+fn to_ansi(buf: &Buffer) -> String {
+  let mut rs = String::new();
+  for y in buf.area.top()..buf.area.bottom() {
+    for (_, style, text) in runs(buf, y) {
+      rs.push_str(&sgr(&style));
+      rs.push_str(&text);
+    }
+    rs.push_str("\x1b[0m\n");
+  }
+  rs
+}
+
+fn css_style(style: &Style) -> String {
+  let (fg, bg) = style.css_colors();
+  let mut css = format!("color:{};background:{}", fg, bg);
+  if style.mods.contains(Modifier::BOLD) {
+    css.push_str(";font-weight:bold");
+  }
+  if style.mods.contains(Modifier::ITALIC) {
+    css.push_str(";font-style:italic");
+  }
+  if style.mods.contains(Modifier::UNDERLINED) {
+    css.push_str(";text-decoration:underline");
+  }
+  css
+}
+
+fn to_html(buf: &Buffer) -> String {
+  let mut rs = String::new();
+  rs.push_str(
+    "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>macmon</title></head>\n",
+  );
+  let _ = writeln!(
+    rs,
+    "<body style=\"background:{}\">\n<pre style=\"font-family:Menlo,Consolas,monospace;font-size:14px;line-height:1.2;color:{};background:{}\">",
+    DEFAULT_BG, DEFAULT_FG, DEFAULT_BG
+  );
+
+  for y in buf.area.top()..buf.area.bottom() {
+    for (_, style, text) in runs(buf, y) {
+      let _ = write!(rs, "<span style=\"{}\">{}</span>", css_style(&style), escape(&text));
+    }
+    rs.push('\n');
+  }
+
+  rs.push_str("</pre>\n</body>\n</html>\n");
+  rs
+}
+
+fn to_svg(buf: &Buffer) -> String {
+  let (w, h) = (buf.area.width as f64 * CELL_W, buf.area.height as f64 * CELL_H);
+
+  let mut rs = String::new();
+  let _ = writeln!(
+    rs,
+    "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.1} {h:.1}\">"
+  );
+  let _ = writeln!(rs, "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>", DEFAULT_BG);
+  let _ = writeln!(
+    rs,
+    "<g font-family=\"Menlo,Consolas,monospace\" font-size=\"14px\" xml:space=\"preserve\">"
+  );
+
+  for y in buf.area.top()..buf.area.bottom() {
+    let row = (y - buf.area.top()) as f64;
+    for (x, style, text) in runs(buf, y) {
+      let (fg, bg) = style.css_colors();
+      let col = (x - buf.area.left()) as f64;
+      let width = text.chars().count() as f64 * CELL_W;
+
+      if bg != DEFAULT_BG {
+        let _ = writeln!(
+          rs,
+          "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>",
+          col * CELL_W,
+          row * CELL_H,
+          width,
+          CELL_H,
+          bg
+        );
+      }
+
+      if text.trim().is_empty() {
+        continue;
+      }
+
+      let weight = if style.mods.contains(Modifier::BOLD) { " font-weight=\"bold\"" } else { "" };
+      let _ = writeln!(
+        rs,
+        "<text x=\"{:.1}\" y=\"{:.1}\" fill=\"{}\"{} textLength=\"{:.1}\">{}</text>",
+        col * CELL_W,
+        row * CELL_H + CELL_H * 0.8,
+        fg,
+        weight,
+        width,
+        escape(&text)
+      );
+    }
+  }
+
+  rs.push_str("</g>\n</svg>\n");
+  rs
+}
+
+pub fn export(buf: &Buffer, format: Format) -> String {
+  match format {
+    Format::Text => to_text(buf),
+    Format::Ansi => to_ansi(buf),
+    Format::Svg => to_svg(buf),
+    Format::Html => to_html(buf),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use ratatui::layout::Rect;
+  use ratatui::style::Style as TuiStyle;
+
+  fn buffer() -> Buffer {
+    let mut buf = Buffer::empty(Rect::new(0, 0, 8, 2));
+    buf.set_string(0, 0, "CPU", TuiStyle::new().fg(Color::Green));
+    buf.set_string(4, 0, "<1W>", TuiStyle::new().add_modifier(Modifier::BOLD));
+    buf.set_string(0, 1, "ok", TuiStyle::new().bg(Color::Indexed(196)));
+    buf
+  }
+
+  #[test]
+  fn test_export_text() {
+    assert_eq!(export(&buffer(), Format::Text), "CPU <1W>\nok\n");
+  }
+
+  #[test]
+  fn test_export_ansi() {
+    let rs = export(&buffer(), Format::Ansi);
+    let line = rs.lines().next().unwrap();
+    assert_eq!(line, "\x1b[0;32mCPU\x1b[0m \x1b[0;1m<1W>\x1b[0m");
+    assert!(rs.contains("\x1b[0;48;5;196mok"));
+  }
+
+  #[test]
+  fn test_export_html_svg() {
+    let rs = export(&buffer(), Format::Html);
+    assert!(rs.contains("<span style=\"color:#00cd00;background:#1e1e1e\">CPU</span>"));
+    assert!(rs.contains("font-weight:bold\">&lt;1W&gt;</span>"));
+
+    let rs = export(&buffer(), Format::Svg);
+    assert!(rs.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"67\" height=\"34\""));
+    assert!(rs.contains(">&lt;1W&gt;</text>"));
+    assert!(rs.contains("fill=\"#ff0000\"/>"));
+  }
+}
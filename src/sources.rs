@@ -11,7 +11,9 @@ use std::{
 
 use core_foundation::{
   array::{CFArrayGetCount, CFArrayGetValueAtIndex, CFArrayRef},
-  base::{kCFAllocatorDefault, kCFAllocatorNull, CFAllocatorRef, CFRange, CFRelease, CFTypeRef},
+  base::{
+    kCFAllocatorDefault, kCFAllocatorNull, CFAllocatorRef, CFRange, CFRelease, CFRetain, CFTypeRef,
+  },
   data::{CFDataGetBytes, CFDataGetLength, CFDataRef},
   dictionary::{
     kCFTypeDictionaryKeyCallBacks, kCFTypeDictionaryValueCallBacks, CFDictionaryCreate,
@@ -119,6 +121,7 @@ extern "C" {
   fn IOReportChannelGetChannelName(a: CFDictionaryRef) -> CFStringRef;
   fn IOReportSimpleGetIntegerValue(a: CFDictionaryRef, b: i32) -> i64;
   fn IOReportChannelGetUnitLabel(a: CFDictionaryRef) -> CFStringRef;
+  fn IOReportChannelGetFormat(a: CFDictionaryRef) -> i32;
   fn IOReportStateGetCount(a: CFDictionaryRef) -> i32;
   fn IOReportStateGetNameForIndex(a: CFDictionaryRef, b: i32) -> CFStringRef;
   fn IOReportStateGetResidency(a: CFDictionaryRef, b: i32) -> i64;
@@ -171,6 +174,15 @@ pub fn cfio_get_residencies(item: CFDictionaryRef) -> Vec<(String, i64)> {
   res
 }
 
+// kIOReportFormat*: 1 – simple, 2 – state, 3 – histogram, 4 – simple array
+pub fn cfio_get_format(item: CFDictionaryRef) -> i32 {
+  unsafe { IOReportChannelGetFormat(item) }
+}
+
+pub fn cfio_get_integer(item: CFDictionaryRef) -> i64 {
+  unsafe { IOReportSimpleGetIntegerValue(item, 0) }
+}
+
 pub fn cfio_watts(item: CFDictionaryRef, unit: &String, duration: u64) -> WithError<f32> {
   let val = unsafe { IOReportSimpleGetIntegerValue(item, 0) } as f32;
   let val = val / (duration as f32 / 1000.0);
@@ -556,6 +568,12 @@ impl IOReport {
     }
   }
 
+  // Subscribed channels without taking a sample, items have no values
+  pub fn get_channels(&self) -> IOReportIterator {
+    unsafe { CFRetain(self.chan as _) };
+    IOReportIterator::new(self.chan)
+  }
+
   fn raw_sample(&self) -> (CFDictionaryRef, std::time::Instant) {
     (unsafe { IOReportCreateSamples(self.subs, self.chan, null()) }, std::time::Instant::now())
   }
@@ -0,0 +1,261 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::metrics::{zero_div, Metrics};
+use crate::units::{Units, GIB};
+
+// MARK: Accumulator
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Stat {
+  sum: f64,
+  count: u64,
+  max: f64,
+  energy: f64, // value * seconds
+}
+
+impl Stat {
+  fn push(&mut self, value: f64, dt: f64) {
+    self.max = if self.count == 0 { value } else { self.max.max(value) };
+    self.sum += value;
+    self.count += 1;
+    self.energy += value * dt;
+  }
+
+  fn avg(&self) -> f64 {
+    zero_div(self.sum, self.count as f64)
+  }
+}
+
+// Whole session stats, unlike history they are not limited by retention
+#[derive(Debug, Default)]
+pub struct Session {
+  start: u64,        // msec
+  last: Option<u64>, // msec, time of the previous sample
+  samples: u64,
+  stats: BTreeMap<&'static str, Stat>,
+  temps: BTreeMap<String, Stat>, // custom sensor groups
+}
+
+impl Session {
+  pub fn new(start: u64) -> Self {
+    Self { start, ..Default::default() }
+  }
+
+  fn stat(&mut self, name: &'static str, value: Option<f64>, dt: f64) {
+    if let Some(value) = value {
+      self.stats.entry(name).or_default().push(value, dt);
+    }
+  }
+
+  pub fn push(&mut self, ts: u64, data: &Metrics) {
+    // energy is integrated over time since the previous sample
+    let dt = self.last.map_or(0.0, |x| ts.saturating_sub(x) as f64 / 1000.0);
+    self.last = Some(ts);
+    self.samples += 1;
+
+    let f = |x: Option<f32>| x.map(|x| x as f64);
+    self.stat("cpu_power", f(data.cpu_power), dt);
+    self.stat("gpu_power", f(data.gpu_power), dt);
+    self.stat("ane_power", f(data.ane_power), dt);
+    self.stat("all_power", f(data.all_power), dt);
+    self.stat("sys_power", f(data.sys_power), dt);
+
+    self.stat("ecpu_usage", data.ecpu_usage.map(|x| x.1 as f64 * 100.0), dt);
+    self.stat("pcpu_usage", data.pcpu_usage.map(|x| x.1 as f64 * 100.0), dt);
+    self.stat("gpu_usage", data.gpu_usage.map(|x| x.1 as f64 * 100.0), dt);
+
+    self.stat("cpu_temp", data.temp.as_ref().and_then(|x| x.cpu_temp_avg).map(|x| x as f64), dt);
+    self.stat("gpu_temp", data.temp.as_ref().and_then(|x| x.gpu_temp_avg).map(|x| x as f64), dt);
+    for (name, x) in data.temp.iter().flat_map(|x| x.custom_groups()) {
+      self.temps.entry(name.clone()).or_default().push(x.avg as f64, dt);
+    }
+
+    self.stat("ram", data.memory.as_ref().map(|x| x.ram_usage as f64 / GIB), dt);
+    self.stat("swap", data.memory.as_ref().map(|x| x.swap_usage as f64 / GIB), dt);
+  }
+
+  pub fn summary(&self, now: u64, units: Units) -> Summary {
+    let get = |name: &str| self.stats.get(name).copied();
+
+    let mut power = BTreeMap::new();
+    let names = [
+      ("cpu", "cpu_power"),
+      ("gpu", "gpu_power"),
+      ("ane", "ane_power"),
+      ("all", "all_power"),
+      ("sys", "sys_power"),
+    ];
+    for (key, name) in names {
+      if let Some(x) = get(name) {
+        let item = PowerSummary { avg: x.avg(), max: x.max, energy: x.energy / 3600.0 };
+        power.insert(key.to_string(), item);
+      }
+    }
+
+    let mut usage = BTreeMap::new();
+    for (key, name) in [("ecpu", "ecpu_usage"), ("pcpu", "pcpu_usage"), ("gpu", "gpu_usage")] {
+      if let Some(x) = get(name) {
+        usage.insert(key.to_string(), x.avg());
+      }
+    }
+
+    Summary {
+      duration: now.saturating_sub(self.start) as f64 / 1000.0,
+      samples: self.samples,
+      power,
+      usage,
+      max_cpu_temp: get("cpu_temp").map(|x| x.max),
+      max_gpu_temp: get("gpu_temp").map(|x| x.max),
+      max_temps: self.temps.iter().map(|(k, v)| (k.clone(), v.max)).collect(),
+      max_ram: get("ram").map(|x| x.max),
+      max_swap: get("swap").map(|x| x.max),
+      units,
+    }
+  }
+}
+
+// MARK: Summary
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct PowerSummary {
+  pub avg: f64,    // W
+  pub max: f64,    // W
+  pub energy: f64, // Wh
+}
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+  pub duration: f64, // seconds
+  pub samples: u64,
+  pub power: BTreeMap<String, PowerSummary>, // cpu / gpu / ane / all / sys
+  pub usage: BTreeMap<String, f64>,          // ecpu / pcpu / gpu, average %
+  pub max_cpu_temp: Option<f64>,             // °C
+  pub max_gpu_temp: Option<f64>,             // °C
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  pub max_temps: BTreeMap<String, f64>, // °C, custom sensor groups
+  pub max_ram: Option<f64>,                  // GiB (1024³ bytes)
+  pub max_swap: Option<f64>,                 // GiB
+  #[serde(skip)]
+  pub units: Units,          // of the text output, JSON keeps °C / GiB / W
+}
+
+fn fmt_duration(secs: u64) -> String {
+  match secs {
+    x if x >= 3600 => format!("{}h {}m {}s", x / 3600, x % 3600 / 60, x % 60),
+    x if x >= 60 => format!("{}m {}s", x / 60, x % 60),
+    x => format!("{}s", x),
+  }
+}
+
+impl Summary {
+  pub fn save(&self, path: &str) -> std::io::Result<()> {
+    let file = std::fs::File::create(path)?;
+    serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)?;
+    Ok(())
+  }
+}
+
+impl fmt::Display for Summary {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let duration = fmt_duration(self.duration as u64);
+    writeln!(f, "Session: {}, {} samples", duration, self.samples)?;
+
+    let names = [("cpu", "CPU"), ("gpu", "GPU"), ("ane", "ANE"), ("all", "All"), ("sys", "Total")];
+    let power = names.iter().filter_map(|(key, label)| {
+      let x = self.power.get(*key)?;
+      let (avg, max) = (self.units.fmt_power(x.avg, 2), self.units.fmt_power(x.max, 2));
+      Some(format!("{} {} / {} / {:.3}Wh", label, avg, max, x.energy))
+    });
+    let power = power.collect::<Vec<_>>();
+    if !power.is_empty() {
+      writeln!(f, "Power (avg / max / energy): {}", power.join(" · "))?;
+    }
+
+    let mut temp =
+      vec![("CPU".to_string(), self.max_cpu_temp), ("GPU".to_string(), self.max_gpu_temp)];
+    temp.extend(self.max_temps.iter().map(|(k, v)| (k.to_uppercase(), Some(*v))));
+    let temp = temp
+      .iter()
+      .filter_map(|(label, x)| Some(format!("{} {}", label, self.units.fmt_temp((*x)?, 1))));
+    let temp = temp.collect::<Vec<_>>();
+    if !temp.is_empty() {
+      writeln!(f, "Peak temperature: {}", temp.join(" · "))?;
+    }
+
+    if let (Some(ram), Some(swap)) = (self.max_ram, self.max_swap) {
+      let (ram, swap) = (self.units.size(ram * GIB), self.units.size(swap * GIB));
+      let unit = self.units.size_label();
+      writeln!(f, "Peak memory: RAM {:.2}{} · Swap {:.2}{}", ram, unit, swap, unit)?;
+    }
+
+    let names = [("ecpu", "E-CPU"), ("pcpu", "P-CPU"), ("gpu", "GPU")];
+    let usage = names
+      .iter()
+      .filter_map(|(key, label)| Some(format!("{} {:.0}%", label, self.usage.get(*key)?)));
+    let usage = usage.collect::<Vec<_>>();
+    if !usage.is_empty() {
+      writeln!(f, "Average usage: {}", usage.join(" · "))?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::metrics::{MemMetrics, TempGroup, TempMetrics};
+
+  fn metrics(cpu_power: f32, cpu_temp: f32) -> Metrics {
+    Metrics {
+      cpu_power: Some(cpu_power),
+      pcpu_usage: Some((3000, 0.5)),
+      temp: Some(TempMetrics {
+        cpu_temp_avg: Some(cpu_temp),
+        gpu_temp_avg: Some(40.0),
+        groups: BTreeMap::from([(
+          "ssd".to_string(),
+          TempGroup { avg: 35.0, ..Default::default() },
+        )]),
+      }),
+      memory: Some(MemMetrics { ram_usage: 4 * GIB as u64, swap_usage: 0, ..Default::default() }),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_session_summary() {
+    let mut session = Session::new(0);
+    session.push(1000, &metrics(2.0, 50.0));
+    session.push(2000, &metrics(4.0, 70.0));
+    session.push(3800, &metrics(3.0, 60.0));
+
+    let rs = session.summary(3800, Units::default());
+    assert_eq!(rs.duration, 3.8);
+    assert_eq!(rs.samples, 3);
+    assert_eq!(rs.max_cpu_temp, Some(70.0));
+    assert_eq!(rs.max_ram, Some(4.0));
+    assert_eq!(rs.usage.get("pcpu"), Some(&50.0));
+    assert!(!rs.power.contains_key("gpu"));
+
+    // energy: 4W * 1s + 3W * 1.8s = 9.4Ws
+    let cpu = rs.power["cpu"];
+    assert_eq!((cpu.avg, cpu.max), (3.0, 4.0));
+    assert!((cpu.energy - 9.4 / 3600.0).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_summary_text() {
+    let mut session = Session::new(0);
+    session.push(0, &metrics(2.0, 50.0));
+
+    let text = session.summary(125_000, Units::default()).to_string();
+    assert!(text.starts_with("Session: 2m 5s, 1 samples\n"));
+    assert!(text.contains("Power (avg / max / energy): CPU 2.00W / 2.00W / 0.000Wh\n"));
+    assert!(text.contains("Peak temperature: CPU 50.0°C · GPU 40.0°C · SSD 35.0°C\n"));
+    assert!(text.contains("Average usage: P-CPU 50%\n"));
+  }
+}
@@ -0,0 +1,159 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::symbols;
+use serde::{Deserialize, Serialize};
+
+// Single color themes, cycled with `c` key before the multi-color ones
+const MONO_THEMES: [(&str, Color); 7] = [
+  ("green", Color::Green),
+  ("yellow", Color::Yellow),
+  ("red", Color::Red),
+  ("blue", Color::Blue),
+  ("magenta", Color::Magenta),
+  ("cyan", Color::Cyan),
+  ("mono", Color::Reset),
+];
+
+pub const DEFAULT_THEME: &str = "green";
+
+pub const ASCII_BORDER: symbols::border::Set = symbols::border::Set {
+  top_left: "+",
+  top_right: "+",
+  bottom_left: "+",
+  bottom_right: "+",
+  vertical_left: "|",
+  vertical_right: "|",
+  horizontal_top: "-",
+  horizontal_bottom: "-",
+};
+
+pub const ASCII_LINE: symbols::line::Set = symbols::line::Set {
+  vertical: "|",
+  horizontal: "-",
+  top_right: "+",
+  top_left: "+",
+  bottom_right: "+",
+  bottom_left: "+",
+  vertical_left: "+",
+  vertical_right: "+",
+  horizontal_down: "+",
+  horizontal_up: "+",
+  cross: "+",
+};
+
+pub const ASCII_BAR: symbols::bar::Set = symbols::bar::Set {
+  full: "#",
+  seven_eighths: "#",
+  three_quarters: "=",
+  five_eighths: "=",
+  half: "-",
+  three_eighths: "-",
+  one_quarter: "_",
+  one_eighth: "_",
+  empty: " ",
+};
+
+// Chart widget has no symbol sets, its dots, axes and legend border are replaced after render
+pub fn ascii_chart(buf: &mut Buffer, area: Rect) {
+  for pos in area.positions() {
+    let symbol = match buf[pos].symbol() {
+      "•" => "*",
+      "│" => "|",
+      "─" => "-",
+      "┌" | "┐" | "└" | "┘" => "+",
+      _ => continue,
+    };
+    buf[pos].set_symbol(symbol);
+  }
+}
+
+// Missing fields in user-defined themes fall back to terminal default color
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Theme {
+  pub border: Color,
+  pub title: Color,
+  pub label: Color, // axis labels and plain text
+  pub background: Color,
+  pub cpu: Color,
+  pub gpu: Color,
+  pub ane: Color,
+  pub memory: Color,
+  pub total: Color, // all / system power
+  pub other: Color, // other power channels
+  pub cursor: Color,
+  pub warning: Color, // metric above warning threshold
+  pub critical: Color,
+}
+
+impl Theme {
+  fn mono(color: Color) -> Self {
+    let cursor = if color == Color::Reset { Color::Reset } else { Color::White };
+    let (warning, critical) = match color {
+      Color::Yellow => (Color::LightYellow, Color::Red),
+      Color::Red => (Color::Yellow, Color::LightRed),
+      _ => (Color::Yellow, Color::Red),
+    };
+    Self {
+      border: color,
+      title: color,
+      label: color,
+      background: Color::Reset,
+      cpu: color,
+      gpu: color,
+      ane: color,
+      memory: color,
+      total: color,
+      other: color,
+      cursor,
+      warning,
+      critical,
+    }
+  }
+
+  fn rainbow() -> Self {
+    Self {
+      border: Color::DarkGray,
+      title: Color::White,
+      label: Color::Gray,
+      background: Color::Reset,
+      cpu: Color::Green,
+      gpu: Color::Magenta,
+      ane: Color::Cyan,
+      memory: Color::Blue,
+      total: Color::Yellow,
+      other: Color::LightBlue,
+      cursor: Color::Red,
+      warning: Color::LightYellow,
+      critical: Color::LightRed,
+    }
+  }
+
+  pub fn builtin(name: &str) -> Option<Self> {
+    match name {
+      "rainbow" => Some(Self::rainbow()),
+      _ => MONO_THEMES.iter().find(|x| x.0 == name).map(|x| Self::mono(x.1)),
+    }
+  }
+
+  pub fn builtin_names() -> Vec<String> {
+    let names = MONO_THEMES.iter().map(|x| x.0.to_string());
+    names.chain(["rainbow".to_string()]).collect()
+  }
+
+  // Name of single color theme, used to migrate old `color` option
+  pub fn name_for_color(color: Color) -> Option<&'static str> {
+    MONO_THEMES.iter().find(|x| x.1 == color).map(|x| x.0)
+  }
+
+  // https://no-color.org
+  pub fn no_color() -> bool {
+    std::env::var("NO_COLOR").is_ok_and(|x| !x.is_empty())
+  }
+
+  // alert levels are still visible by text modifiers
+  pub fn colorless() -> Self {
+    Self { warning: Color::Reset, critical: Color::Reset, ..Self::mono(Color::Reset) }
+  }
+}
@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+
+// bytes, thresholds and JSON outputs keep sizes in GiB
+pub const GIB: f64 = (1024 * 1024 * 1024) as f64;
+const GB: f64 = 1_000_000_000.0;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TempUnit {
+  #[default]
+  Celsius,
+  Fahrenheit,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeUnit {
+  #[default]
+  Gib, // 1024³ bytes
+  Gb, // 10⁹ bytes
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FreqUnit {
+  #[default]
+  Mhz,
+  Ghz,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerUnit {
+  #[default]
+  W,
+  Auto, // mW below 1 W, e.g. for idle ANE
+}
+
+// Display units of TUI and text outputs, metrics and JSON stay in °C / bytes / MHz / W
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Units {
+  pub temp: TempUnit,
+  pub size: SizeUnit,
+  pub freq: FreqUnit,
+  pub power: PowerUnit,
+}
+
+impl Units {
+  pub fn temp(&self, celsius: f64) -> f64 {
+    match self.temp {
+      TempUnit::Celsius => celsius,
+      TempUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+    }
+  }
+
+  pub fn temp_label(&self) -> &'static str {
+    match self.temp {
+      TempUnit::Celsius => "°C",
+      TempUnit::Fahrenheit => "°F",
+    }
+  }
+
+  // e.g. "45.0°C"
+  pub fn fmt_temp(&self, celsius: f64, prec: usize) -> String {
+    format!("{:.p$}{}", self.temp(celsius), self.temp_label(), p = prec)
+  }
+
+  pub fn size(&self, bytes: f64) -> f64 {
+    match self.size {
+      SizeUnit::Gib => bytes / GIB,
+      SizeUnit::Gb => bytes / GB,
+    }
+  }
+
+  pub fn size_label(&self) -> &'static str {
+    match self.size {
+      SizeUnit::Gib => "GiB",
+      SizeUnit::Gb => "GB",
+    }
+  }
+
+  pub fn freq(&self, mhz: f64) -> f64 {
+    match self.freq {
+      FreqUnit::Mhz => mhz,
+      FreqUnit::Ghz => mhz / 1000.0,
+    }
+  }
+
+  // Fixed width for panel titles, e.g. "1000 MHz" or "1.00 GHz"
+  pub fn fmt_freq(&self, mhz: f64) -> String {
+    match self.freq {
+      FreqUnit::Mhz => format!("{:4.0} MHz", mhz),
+      FreqUnit::Ghz => format!("{:4.2} GHz", mhz / 1000.0),
+    }
+  }
+
+  // (unit, precision) for frequency values
+  pub fn freq_label(&self) -> (&'static str, usize) {
+    match self.freq {
+      FreqUnit::Mhz => ("MHz", 0),
+      FreqUnit::Ghz => ("GHz", 2),
+    }
+  }
+
+  // e.g. "1.50W", or "350mW" in auto mode
+  pub fn fmt_power(&self, watts: f64, prec: usize) -> String {
+    match self.power {
+      PowerUnit::Auto if watts.abs() < 1.0 => format!("{:.0}mW", watts * 1000.0),
+      _ => format!("{:.p$}W", watts, p = prec),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_default_units() {
+    let units = Units::default();
+    assert_eq!(units.fmt_temp(45.0, 1), "45.0°C");
+    assert_eq!(units.size(8.0 * GIB), 8.0);
+    assert_eq!(units.fmt_freq(1000.0), "1000 MHz");
+    assert_eq!(units.fmt_power(0.35, 2), "0.35W");
+  }
+
+  #[test]
+  fn test_custom_units() {
+    let units = Units {
+      temp: TempUnit::Fahrenheit,
+      size: SizeUnit::Gb,
+      freq: FreqUnit::Ghz,
+      power: PowerUnit::Auto,
+    };
+
+    assert_eq!(units.fmt_temp(100.0, 0), "212°F");
+    assert_eq!(units.size(2.0 * GIB), 2.147483648);
+    assert_eq!(units.fmt_freq(3228.0), "3.23 GHz");
+    assert_eq!(units.fmt_power(0.0347, 2), "35mW");
+    assert_eq!(units.fmt_power(12.5, 2), "12.50W");
+  }
+
+  #[test]
+  fn test_units_config() {
+    let units: Units = serde_json::from_str(r#"{ "temp": "fahrenheit" }"#).unwrap();
+    assert_eq!(units.temp, TempUnit::Fahrenheit);
+    assert_eq!(units.size, SizeUnit::Gib);
+  }
+}